@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+
+// Refers to a value stored in an arena. The generation changes every time a slot is reused, so a
+// handle to a removed value never finds whatever replaced it.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+pub struct Handle {
+    index: u32,
+    generation: u32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Slot<T> {
+    generation: u32,
+    value: Option<T>,
+}
+
+// Stores values under handles that stay valid until the value is removed, however many other
+// values are added or removed in the meantime
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Arena<T> {
+    slots: Vec<Slot<T>>,
+    // The indices of empty slots, reused most recently freed first
+    free: Vec<u32>,
+    len: usize,
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Arena<T> {
+        return Arena {
+            slots: vec![],
+            free: vec![],
+            len: 0,
+        }
+    }
+
+    // Stores a value, returning the handle to get it back with
+    pub fn insert(&mut self, value: T) -> Handle {
+        self.len += 1;
+        if let Some(index) = self.free.pop() {
+            let slot: &mut Slot<T> = &mut self.slots[index as usize];
+            slot.value = Some(value);
+            return Handle { index: index, generation: slot.generation };
+        }
+        self.slots.push(Slot { generation: 0, value: Some(value) });
+        return Handle { index: self.slots.len() as u32 - 1, generation: 0 };
+    }
+
+    // Removes and returns the value with the given handle, if it is still there
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        let slot: &mut Slot<T> = self.slots.get_mut(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        let value: T = slot.value.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(handle.index);
+        self.len -= 1;
+        return Some(value);
+    }
+
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        let slot: &Slot<T> = self.slots.get(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        return slot.value.as_ref();
+    }
+
+    pub fn get_mut(&mut self, handle: Handle) -> Option<&mut T> {
+        let slot: &mut Slot<T> = self.slots.get_mut(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        return slot.value.as_mut();
+    }
+
+    // Returns the number of values stored
+    pub fn len(&self) -> usize {
+        return self.len;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.len == 0;
+    }
+
+    // Returns every value with its handle, in the order of their slots
+    pub fn iter(&self) -> impl Iterator<Item = (Handle, &T)> {
+        return self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let handle: Handle = Handle { index: index as u32, generation: slot.generation };
+            return slot.value.as_ref().map(|value| (handle, value));
+        });
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Handle, &mut T)> {
+        return self.slots.iter_mut().enumerate().filter_map(|(index, slot)| {
+            let handle: Handle = Handle { index: index as u32, generation: slot.generation };
+            return slot.value.as_mut().map(|value| (handle, value));
+        });
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        return self.slots.iter().filter_map(|slot| slot.value.as_ref());
+    }
+
+    // Removes every value the function returns false for
+    pub fn retain(&mut self, mut keep: impl FnMut(&T) -> bool) {
+        let handles: Vec<Handle> = self.iter().filter(|(_, value)| !keep(value)).map(|(handle, _)| handle).collect();
+        for handle in handles {
+            self.remove(handle);
+        }
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Arena<T> {
+        return Arena::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_handle_finds_nothing() {
+        let mut arena: Arena<&str> = Arena::new();
+        let old: Handle = arena.insert("old");
+        assert_eq!(arena.remove(old), Some("old"));
+        // The new value reuses the old slot, but not under the old handle
+        let new: Handle = arena.insert("new");
+        assert_eq!(arena.get(old), None);
+        assert!(arena.get_mut(old).is_none());
+        assert_eq!(arena.remove(old), None);
+        assert_eq!(arena.get(new), Some(&"new"));
+        assert_eq!(arena.len(), 1);
+    }
+}
@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+
+// The bloon types shipped with the game, used when there is no bloons file next to the game
+pub const DEFAULT_BLOONS: &str = include_str!("../assets/bloons.ron");
+
+// The index of a bloon type in the loaded list
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct BloonId(pub usize);
+
+// How many hits it takes to pop each layer of a fortified bloon
+const FORTIFIED_HITS: u32 = 2;
+
+// Properties a bloon can have on top of its type, given per spawn group in the rounds file
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Modifiers {
+    // Can only be targeted by towers that detect camo
+    pub camo: bool,
+    // Can't be hurt by sharp damage. Only the outer layer is lead.
+    pub lead: bool,
+    // Grows back a layer every few seconds, up to the type it was sent as
+    pub regrow: bool,
+    // Takes extra hits to pop each layer
+    pub fortified: bool,
+}
+
+impl Modifiers {
+    // Returns the number of hits it takes to pop each layer
+    pub fn hits_per_layer(self) -> u32 {
+        if self.fortified {
+            return FORTIFIED_HITS;
+        }
+        return 1;
+    }
+
+    // Returns the modifiers passed on to the children when a layer pops, which is everything
+    // but lead
+    pub fn children(self) -> Modifiers {
+        return Modifiers {
+            lead: false,
+            ..self
+        };
+    }
+}
+
+// A bloon type as written in the data file, with its children given by id
+#[derive(Deserialize)]
+struct BloonEntry {
+    id: String,
+    name: String,
+    speed: f32,
+    colour: (u8,u8,u8),
+    radius: f32,
+    reward: u32,
+    children: Vec<String>,
+}
+
+pub struct BloonType {
+    pub id: String,
+    pub name: String,
+    pub speed: f32,
+    pub colour: (u8,u8,u8),
+    pub radius: f32,
+    pub reward: u32,
+    pub children: Vec<BloonId>,
+    // The number of layers popped to clear this bloon and everything it pops into
+    pub layers: u32,
+}
+
+pub struct BloonTypes {
+    types: Vec<BloonType>,
+}
+
+impl BloonTypes {
+    // Parses a list of bloon types, checking that every child exists and that popping always ends
+    pub fn parse(text: &str) -> Result<BloonTypes, String> {
+        let entries: Vec<BloonEntry> = ron::from_str(text).map_err(|e| e.to_string())?;
+        let mut types: Vec<BloonType> = vec![];
+        for entry in &entries {
+            if entries.iter().filter(|other| other.id == entry.id).count() > 1 {
+                return Err(format!("bloon '{}' is defined more than once", entry.id));
+            }
+            let mut children: Vec<BloonId> = vec![];
+            for child in &entry.children {
+                match entries.iter().position(|other| &other.id == child) {
+                    Some(pos) => children.push(BloonId(pos)),
+                    None => return Err(format!("bloon '{}' has unknown child '{}'", entry.id, child)),
+                }
+            }
+            types.push(BloonType {
+                id: entry.id.clone(),
+                name: entry.name.clone(),
+                speed: entry.speed,
+                colour: entry.colour,
+                radius: entry.radius,
+                reward: entry.reward,
+                children: children,
+                layers: 0,
+            });
+        }
+        let mut bloons = BloonTypes { types: types };
+        for pos in 0..bloons.types.len() {
+            if bloons.pops_into(BloonId(pos), BloonId(pos)) {
+                return Err(format!("bloon '{}' pops into itself", bloons.types[pos].id));
+            }
+        }
+        for pos in 0..bloons.types.len() {
+            bloons.types[pos].layers = bloons.count_layers(BloonId(pos));
+        }
+        return Ok(bloons);
+    }
+
+    // Returns true if popping the given bloon eventually produces the target bloon
+    fn pops_into(&self, bloon: BloonId, target: BloonId) -> bool {
+        let mut seen: Vec<BloonId> = vec![];
+        let mut stack: Vec<BloonId> = self.get(bloon).children.clone();
+        while let Some(child) = stack.pop() {
+            if child == target {
+                return true;
+            }
+            if !seen.contains(&child) {
+                seen.push(child);
+                stack.extend(self.get(child).children.iter().copied());
+            }
+        }
+        return false;
+    }
+
+    fn count_layers(&self, kind: BloonId) -> u32 {
+        let children: &Vec<BloonId> = &self.get(kind).children;
+        return 1 + children.iter().map(|&child| self.count_layers(child)).sum::<u32>();
+    }
+
+    // Pops a bloon of the given type and passes the leftover damage on to each of its children.
+    // Children that survive are added to out, and the reward for every layer popped is returned.
+    pub fn pop(&self, kind: BloonId, damage: u32, out: &mut Vec<BloonId>) -> u32 {
+        let bloon = self.get(kind);
+        let mut reward: u32 = bloon.reward;
+        for &child in &bloon.children {
+            if damage > 0 {
+                reward += self.pop(child, damage - 1, out);
+            } else {
+                out.push(child);
+            }
+        }
+        return reward;
+    }
+
+    pub fn get(&self, id: BloonId) -> &BloonType {
+        return &self.types[id.0];
+    }
+
+    // Returns the type one layer above the given bloon on the way up to the top type, if the
+    // given bloon is something the top type pops into
+    pub fn parent_towards(&self, top: BloonId, kind: BloonId) -> Option<BloonId> {
+        for &child in &self.get(top).children {
+            if child == kind {
+                return Some(top);
+            }
+            if let Some(parent) = self.parent_towards(child, kind) {
+                return Some(parent);
+            }
+        }
+        return None;
+    }
+
+    // Returns the id of every bloon type, in order
+    pub fn ids(&self) -> Vec<String> {
+        return self.types.iter().map(|bloon| bloon.id.clone()).collect();
+    }
+
+    // Returns the bloon type with the given id
+    pub fn find(&self, id: &str) -> Option<BloonId> {
+        return self.types.iter().position(|bloon| bloon.id == id).map(BloonId);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pop_passes_overkill_to_children() {
+        let bloons: BloonTypes = BloonTypes::parse(DEFAULT_BLOONS).expect("the built in bloons parse");
+        let find = |id: &str| bloons.find(id).expect("the bloon exists");
+        let mut out: Vec<BloonId> = vec![];
+        // One damage pops a layer
+        assert_eq!(bloons.pop(find("green"), 0, &mut out), 1);
+        assert_eq!(out, vec![find("blue")]);
+        // Two more go through blue and red, leaving nothing
+        out.clear();
+        assert_eq!(bloons.pop(find("green"), 2, &mut out), 3);
+        assert!(out.is_empty());
+        // Damage left over is passed to each child, so a black pops both its pinks
+        out.clear();
+        assert_eq!(bloons.pop(find("black"), 1, &mut out), 3);
+        assert_eq!(out, vec![find("yellow"), find("yellow")]);
+    }
+}
@@ -0,0 +1,34 @@
+// Simulation steps per second
+pub const TICK_RATE: u32 = 60;
+
+// The length of one simulation step in seconds
+pub const DT: f32 = 1.0 / TICK_RATE as f32;
+
+// The most steps run in one frame, so a long stall doesn't freeze the game catching up
+const MAX_STEPS: u32 = 8;
+
+// Converts variable frame times into a whole number of fixed simulation steps
+pub struct Clock {
+    accumulator: f32,
+}
+
+impl Clock {
+    pub fn new() -> Clock {
+        return Clock {
+            accumulator: 0.0,
+        }
+    }
+    // Adds the frame time and returns how many steps should be run this frame
+    pub fn advance(&mut self, frame_time: f32) -> u32 {
+        self.accumulator += frame_time;
+        let mut steps: u32 = 0;
+        while self.accumulator >= DT && steps < MAX_STEPS {
+            self.accumulator -= DT;
+            steps += 1;
+        }
+        if steps == MAX_STEPS {
+            self.accumulator = 0.0;
+        }
+        return steps;
+    }
+}
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+use crate::arena::Handle;
+use crate::tower_type::TowerId;
+
+// An action the player takes in a game. Everything the player does that changes the game is one
+// of these, so the game can be replayed from a list of them.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Command {
+    PlaceTower { kind: TowerId, x: f32, y: f32 },
+    UpgradeTower { tower: Handle, path: usize },
+    SellTower { tower: Handle },
+    CycleTargeting { tower: Handle },
+    StartRound,
+}
@@ -0,0 +1,48 @@
+use crate::bloon::{BloonTypes, DEFAULT_BLOONS};
+use crate::round::{Rounds, DEFAULT_ROUNDS};
+use crate::tower_type::{TowerTypes, DEFAULT_TOWERS};
+use crate::track::{Map, DEFAULT_MAP};
+
+// Where each data file is read from when no other file is given
+pub const MAP_PATH: &str = "assets/maps/simple.ron";
+pub const BLOONS_PATH: &str = "assets/bloons.ron";
+pub const TOWERS_PATH: &str = "assets/towers.ron";
+pub const ROUNDS_PATH: &str = "assets/rounds.ron";
+
+// Everything a game is built from that is read from files rather than compiled in
+pub struct GameData {
+    pub map: Map,
+    pub bloons: BloonTypes,
+    pub towers: TowerTypes,
+    pub rounds: Rounds,
+}
+
+impl GameData {
+    // Loads the game data from the given files, using the default files for any not given
+    pub fn load(map_path: Option<&str>, bloons_path: Option<&str>, towers_path: Option<&str>, rounds_path: Option<&str>) -> Result<GameData, String> {
+        return GameData::load_with_map(load(map_path, MAP_PATH, DEFAULT_MAP, Map::parse)?, bloons_path, towers_path, rounds_path);
+    }
+
+    // Loads everything but the map, which is given, as when test playing a map from the editor
+    pub fn load_with_map(map: Map, bloons_path: Option<&str>, towers_path: Option<&str>, rounds_path: Option<&str>) -> Result<GameData, String> {
+        let bloons: BloonTypes = load(bloons_path, BLOONS_PATH, DEFAULT_BLOONS, BloonTypes::parse)?;
+        let rounds: Rounds = load(rounds_path, ROUNDS_PATH, DEFAULT_ROUNDS, |text| Rounds::parse(text, &bloons))?;
+        return Ok(GameData {
+            map: map,
+            bloons: bloons,
+            towers: load(towers_path, TOWERS_PATH, DEFAULT_TOWERS, TowerTypes::parse)?,
+            rounds: rounds,
+        });
+    }
+}
+
+// Reads and parses the given file, which must exist. With no file given, reads the default path
+// instead, or parses the built in copy if there is nothing there.
+pub fn load<T>(path: Option<&str>, default_path: &str, default: &str, parse: impl Fn(&str) -> Result<T, String>) -> Result<T, String> {
+    let file: &str = path.unwrap_or(default_path);
+    match std::fs::read_to_string(file) {
+        Ok(text) => return parse(&text).map_err(|e| format!("{}: {}", file, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && path.is_none() => return parse(default),
+        Err(e) => return Err(format!("{}: {}", file, e)),
+    }
+}
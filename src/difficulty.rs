@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+// How hard the game is, chosen before it starts
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    // One life, so a single leaked layer loses the game
+    Impoppable,
+}
+
+// The rules a difficulty sets for a game
+pub struct Rules {
+    pub lives: u32,
+    pub money: u32,
+    // Tower and upgrade costs are multiplied by this
+    pub cost_factor: f32,
+    // Bloons move at this multiple of their speed
+    pub speed_factor: f32,
+    // The rounds that must be survived to win, at most the number in the rounds file
+    pub rounds: u32,
+}
+
+impl Difficulty {
+    // Every difficulty, easiest first, as listed in the menu
+    pub const ALL: [Difficulty; 4] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard, Difficulty::Impoppable];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => return "Easy",
+            Difficulty::Medium => return "Medium",
+            Difficulty::Hard => return "Hard",
+            Difficulty::Impoppable => return "Impoppable",
+        }
+    }
+
+    pub fn rules(self) -> Rules {
+        match self {
+            Difficulty::Easy => return Rules { lives: 200, money: 1000, cost_factor: 0.85, speed_factor: 0.9, rounds: 8 },
+            Difficulty::Medium => return Rules { lives: 150, money: 900, cost_factor: 1.0, speed_factor: 1.0, rounds: 10 },
+            Difficulty::Hard => return Rules { lives: 100, money: 800, cost_factor: 1.08, speed_factor: 1.1, rounds: 12 },
+            Difficulty::Impoppable => return Rules { lives: 1, money: 700, cost_factor: 1.2, speed_factor: 1.15, rounds: 12 },
+        }
+    }
+
+    // Returns the difficulty with the given name, ignoring case
+    pub fn find(name: &str) -> Option<Difficulty> {
+        return Difficulty::ALL.into_iter().find(|difficulty| difficulty.name().eq_ignore_ascii_case(name));
+    }
+}
+
+impl std::str::FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Difficulty, String> {
+        return Difficulty::find(text).ok_or(format!("unknown difficulty {}", text));
+    }
+}
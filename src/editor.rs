@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+
+use macroquad::prelude::*;
+
+use crate::clock::DT;
+use crate::data::GameData;
+use crate::difficulty::Difficulty;
+use crate::geometry::{distance_to_segment, pythag_sqrt};
+use crate::player::Player;
+use crate::render::draw_areas;
+use crate::simulation::Simulation;
+use crate::targeting::Targeting;
+use crate::track::{cell_at, default_size, Ground, Map, Track, TRACK_WIDTH};
+
+// How long a message stays on screen, in seconds
+const MESSAGE_TIME: f64 = 2.0;
+
+// How close the mouse must be to a waypoint to grab it, in pixels
+const GRAB_DISTANCE: f32 = 12.0;
+
+// How fast the marker showing the path of the bloons moves along the track, in pixels per second
+const PREVIEW_SPEED: f32 = 100.0;
+
+// The distance between the marks along the track, in pixels
+const MARK_SPACING: f32 = 100.0;
+
+// What clicking on the map does
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Tool {
+    // Left click adds a waypoint or drags one, right click deletes one
+    Track,
+    // Left click paints, right click erases
+    Paint(Ground),
+    // Left click moves the first waypoint, where bloons come from
+    Spawn,
+    // Left click moves the last waypoint, where bloons leave
+    Exit,
+}
+
+// The tools in the order they are picked with the number keys
+const TOOLS: [Tool; 5] = [Tool::Track, Tool::Paint(Ground::Blocked), Tool::Paint(Ground::Water), Tool::Spawn, Tool::Exit];
+
+// The keys that pick each tool
+const TOOL_KEYS: [KeyCode; 5] = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5];
+
+impl Tool {
+    fn name(self) -> &'static str {
+        match self {
+            Tool::Track => return "Track",
+            Tool::Paint(Ground::Blocked) => return "Blocked",
+            Tool::Paint(Ground::Water) => return "Water",
+            Tool::Spawn => return "Spawn",
+            Tool::Exit => return "Exit",
+        }
+    }
+}
+
+// Edits a map file: its track waypoints, blocked and water areas and where bloons enter and
+// leave. The map can be test played with the game data at any point, and is saved in the format
+// the game loads maps from.
+pub struct Editor {
+    // The map file being edited
+    path: String,
+    name: String,
+    size: (f32,f32),
+    waypoints: Vec<(f32,f32)>,
+    areas: HashMap<(i32,i32), Ground>,
+    tool: Tool,
+    // The index of the waypoint being dragged
+    dragging: Option<usize>,
+    // The rest of the game data, loaded to test play the map
+    bloons_path: Option<String>,
+    towers_path: Option<String>,
+    rounds_path: Option<String>,
+    save_path: String,
+    replay_path: String,
+    // The game being played on the map, if it is being test played
+    testing: Option<Player>,
+    // The seconds since the track last changed, which moves the marker along the track
+    preview_time: f32,
+    // The last message to show, its colour and the time it was shown
+    message: Option<(String, Color, f64)>,
+}
+
+impl Editor {
+    // Opens the given map file, or starts a new map named after the file if there is no such file
+    pub fn new(path: String, bloons_path: Option<String>, towers_path: Option<String>, rounds_path: Option<String>, save_path: String, replay_path: String) -> Editor {
+        let name: String = std::path::Path::new(&path).file_stem().map_or(String::from("Custom"), |stem| stem.to_string_lossy().into_owned());
+        let mut editor: Editor = Editor {
+            path: path,
+            name: name,
+            size: default_size(),
+            waypoints: vec![],
+            areas: HashMap::new(),
+            tool: Tool::Track,
+            dragging: None,
+            bloons_path: bloons_path,
+            towers_path: towers_path,
+            rounds_path: rounds_path,
+            save_path: save_path,
+            replay_path: replay_path,
+            testing: None,
+            preview_time: 0.0,
+            message: None,
+        };
+        if std::path::Path::new(&editor.path).exists() {
+            editor.load();
+        }
+        return editor;
+    }
+
+    fn show_message(&mut self, message: String, colour: Color) {
+        self.message = Some((message, colour, get_time()));
+    }
+
+    // Builds the map being edited, which needs a track of at least two waypoints
+    fn map(&self) -> Result<Map, String> {
+        return Map::new(self.name.clone(), self.size, self.waypoints.clone(), self.areas.clone());
+    }
+
+    fn save(&mut self) {
+        let result: Result<(), String> = self.map()
+            .and_then(|map| Map::to_text(&map.name, map.size, map.track.points(), &map.areas))
+            .and_then(|text| std::fs::write(&self.path, text).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => self.show_message(format!("Saved to {}", self.path), GREEN),
+            Err(e) => self.show_message(format!("Couldn't save: {}", e), RED),
+        }
+    }
+
+    // Replaces the map being edited with the one in the map file, read by the game's map loader
+    fn load(&mut self) {
+        let result: Result<Map, String> = std::fs::read_to_string(&self.path).map_err(|e| e.to_string()).and_then(|text| Map::parse(&text));
+        match result {
+            Ok(map) => {
+                self.name = map.name;
+                self.size = map.size;
+                self.waypoints = map.track.points().to_vec();
+                self.areas = map.areas;
+                self.dragging = None;
+                self.preview_time = 0.0;
+                self.show_message(format!("Loaded {}", self.path), GREEN);
+            }
+            Err(e) => self.show_message(format!("Couldn't load {}: {}", self.path, e), RED),
+        }
+    }
+
+    // Starts a game on the map being edited, or goes back to editing if one is being played
+    fn toggle_test(&mut self) {
+        if self.testing.take().is_some() {
+            return;
+        }
+        let data: Result<GameData, String> = self.map().and_then(|map| GameData::load_with_map(map, self.bloons_path.as_deref(), self.towers_path.as_deref(), self.rounds_path.as_deref()));
+        match data {
+            Ok(data) => {
+                let seed: u64 = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|t| t.as_nanos() as u64).unwrap_or(0);
+                let sim: Simulation = Simulation::new(Difficulty::Medium, data, Targeting::First, seed);
+                self.testing = Some(Player::new(sim, self.save_path.clone(), self.replay_path.clone()));
+            }
+            Err(e) => self.show_message(format!("Can't test play: {}", e), RED),
+        }
+    }
+
+    // Returns the index of the waypoint under the mouse, if there is one
+    fn waypoint_at(&self, pos: (f32,f32)) -> Option<usize> {
+        return self.waypoints.iter().position(|&point| pythag_sqrt(point, pos) < GRAB_DISTANCE);
+    }
+
+    // Adds a waypoint at the given position. Clicking on the track splits the segment clicked
+    // on, and clicking anywhere else adds to the end of the track.
+    fn add_waypoint(&mut self, pos: (f32,f32)) {
+        let segment: Option<usize> = self.waypoints.windows(2).position(|pair| distance_to_segment(pos, pair[0], pair[1]) < TRACK_WIDTH / 2.0);
+        match segment {
+            Some(i) => self.waypoints.insert(i + 1, pos),
+            None => self.waypoints.push(pos),
+        }
+        self.preview_time = 0.0;
+    }
+
+    // Runs the game being test played, or moves the marker along the track
+    pub fn update(&mut self, steps: u32) {
+        match &mut self.testing {
+            Some(player) => player.update(steps),
+            None => self.preview_time += steps as f32 * DT,
+        }
+    }
+
+    // Handles input: F4 switches between editing and test playing, the number keys pick a tool,
+    // F5 saves and F9 loads the map file again
+    pub fn input(&mut self) {
+        if is_key_pressed(KeyCode::F4) {
+            self.toggle_test();
+        }
+        if let Some(player) = &mut self.testing {
+            player.input();
+            return;
+        }
+        for (tool, key) in TOOLS.iter().zip(TOOL_KEYS) {
+            if is_key_pressed(key) {
+                self.tool = *tool;
+                self.dragging = None;
+            }
+        }
+        if is_key_pressed(KeyCode::F5) {
+            self.save();
+        }
+        if is_key_pressed(KeyCode::F9) {
+            self.load();
+        }
+        let (mx,my) = mouse_position();
+        let mouse: (f32,f32) = (mx.clamp(0.0, self.size.0), my.clamp(0.0, self.size.1));
+        match self.tool {
+            Tool::Track => {
+                if is_mouse_button_pressed(MouseButton::Left) {
+                    self.dragging = self.waypoint_at(mouse);
+                    if self.dragging.is_none() {
+                        self.add_waypoint(mouse);
+                    }
+                }
+                if let (Some(i), true) = (self.dragging, is_mouse_button_down(MouseButton::Left)) {
+                    self.waypoints[i] = mouse;
+                    self.preview_time = 0.0;
+                } else {
+                    self.dragging = None;
+                }
+                if is_mouse_button_pressed(MouseButton::Right) {
+                    if let Some(i) = self.waypoint_at(mouse) {
+                        self.waypoints.remove(i);
+                        self.preview_time = 0.0;
+                    }
+                }
+            }
+            Tool::Paint(ground) => {
+                if is_mouse_button_down(MouseButton::Left) {
+                    self.areas.insert(cell_at(mouse), ground);
+                } else if is_mouse_button_down(MouseButton::Right) {
+                    self.areas.remove(&cell_at(mouse));
+                }
+            }
+            Tool::Spawn | Tool::Exit => {
+                if is_mouse_button_pressed(MouseButton::Left) {
+                    if self.waypoints.len() < 2 {
+                        self.waypoints.push(mouse);
+                    } else if self.tool == Tool::Spawn {
+                        self.waypoints[0] = mouse;
+                    } else {
+                        let last: usize = self.waypoints.len() - 1;
+                        self.waypoints[last] = mouse;
+                    }
+                    self.preview_time = 0.0;
+                }
+            }
+        }
+    }
+
+    pub fn draw(&self) {
+        if let Some(player) = &self.testing {
+            player.draw();
+            draw_text("Test playing - F4 to go back to the editor", 10.0, screen_height() - 100.0, 20.0, YELLOW);
+            return;
+        }
+        draw_rectangle_lines(0.0, 0.0, self.size.0, self.size.1, 2.0, DARKGRAY);
+        draw_areas(&self.areas);
+        for pair in self.waypoints.windows(2) {
+            draw_line(pair[0].0, pair[0].1, pair[1].0, pair[1].1, TRACK_WIDTH, DARKGRAY);
+        }
+        let length: Option<f32> = Track::new(self.waypoints.clone()).ok().map(|track| {
+            draw_track_preview(&track, self.preview_time);
+            return track.length();
+        });
+        for (i, &(x,y)) in self.waypoints.iter().enumerate() {
+            let colour: Color = if i == 0 { GREEN } else if i == self.waypoints.len() - 1 { RED } else { WHITE };
+            let size: f32 = if self.dragging == Some(i) { 8.0 } else { 6.0 };
+            draw_circle(x, y, size, colour);
+            draw_text(&i.to_string(), x + 8.0, y - 8.0, 16.0, colour);
+        }
+        self.draw_toolbar(length);
+        if let Some((message, colour, time)) = &self.message {
+            if get_time() - time < MESSAGE_TIME {
+                draw_text(message, 10.0, screen_height() - 40.0, 24.0, *colour);
+            }
+        }
+    }
+
+    // Draws the tools along the top of the screen, with the current one highlighted, the length
+    // of the track and the controls
+    fn draw_toolbar(&self, length: Option<f32>) {
+        draw_rectangle(0.0, 0.0, screen_width(), 32.0, Color::new(0.1, 0.1, 0.1, 0.85));
+        let mut x: f32 = 10.0;
+        for (i, tool) in TOOLS.iter().enumerate() {
+            let text: String = format!("{} {}", i + 1, tool.name());
+            let colour: Color = if *tool == self.tool { YELLOW } else { GRAY };
+            draw_text(&text, x, 22.0, 22.0, colour);
+            x += measure_text(&text, None, 22, 1.0).width + 20.0;
+        }
+        let length_text: String = match length {
+            Some(length) => format!("{}  Length: {:.0}px", self.name, length),
+            None => format!("{}  The track needs two waypoints", self.name),
+        };
+        draw_text(&length_text, x + 20.0, 22.0, 22.0, WHITE);
+        draw_text("Left click to add or drag, right click to delete or erase. F5 save, F9 load, F4 test play",
+            10.0, screen_height() - 10.0, 18.0, GRAY);
+    }
+}
+
+// Draws marks along the track every MARK_SPACING pixels, labelled with their distance from the
+// spawn, and a marker moving along it, so the path bloons follow can be checked as it is edited
+fn draw_track_preview(track: &Track, time: f32) {
+    let mut distance: f32 = 0.0;
+    while distance <= track.length() {
+        let (x,y) = track.position_at(distance);
+        draw_circle(x, y, 3.0, LIGHTGRAY);
+        draw_text(&format!("{:.0}", distance), x + 4.0, y + 16.0, 14.0, LIGHTGRAY);
+        distance += MARK_SPACING;
+    }
+    let (x,y) = track.position_at((time * PREVIEW_SPEED) % track.length());
+    draw_circle(x, y, 7.0, PINK);
+}
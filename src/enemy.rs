@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+
+use crate::arena::Handle;
+use crate::bloon::{BloonId, BloonTypes, Modifiers};
+use crate::status::{StatusEffect, Statuses};
+use crate::tower_type::DamageType;
+use crate::track::Track;
+
+// How long a regrow bloon goes without losing a layer before it grows one back, in seconds
+const REGROW_TIME: f32 = 3.0;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Enemy {
+    pub kind: BloonId,
+    // Hits left before the current layer pops
+    pub health: u32,
+    // Layers left to pop, including everything this bloon pops into
+    pub layers: u32,
+    pub modifiers: Modifiers,
+    // The type the enemy was sent as, which regrow bloons grow back up to, and the seconds
+    // since it last grew or lost a layer
+    pub origin: BloonId,
+    regrow_time: f32,
+    pub speed: f32,
+    pub distance: f32,
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+    pub colour: (u8,u8,u8),
+    // The timed effects on the enemy, such as slows and burns
+    pub statuses: Statuses,
+}
+
+impl Enemy {
+    pub fn new(kind: BloonId, modifiers: Modifiers, origin: BloonId, bloons: &BloonTypes) -> Enemy {
+        let bloon = bloons.get(kind);
+        let distance: f32 = 0.0;
+        let x: f32 = 0.0;
+        let y: f32 = 0.0;
+        return Enemy {
+            kind: kind,
+            health: modifiers.hits_per_layer(),
+            layers: bloon.layers,
+            modifiers: modifiers,
+            origin: origin,
+            regrow_time: 0.0,
+            speed: bloon.speed,
+            distance: distance,
+            x: x,
+            y: y,
+            radius: bloon.radius,
+            colour: bloon.colour,
+            statuses: Statuses::new(),
+        };
+    }
+    // Changes the enemy into another bloon type, keeping its place on the track
+    pub fn set_kind(&mut self, kind: BloonId, bloons: &BloonTypes) {
+        let bloon = bloons.get(kind);
+        self.kind = kind;
+        self.health = self.modifiers.hits_per_layer();
+        self.regrow_time = 0.0;
+        self.layers = bloon.layers;
+        self.speed = bloon.speed;
+        self.radius = bloon.radius;
+        self.colour = bloon.colour;
+    }
+    // Puts the enemy at the given distance along the track
+    pub fn place(&mut self, distance: f32, track: &Track) {
+        self.distance = distance;
+        (self.x,self.y) = track.position_at(self.distance);
+    }
+    // Applies an effect caused by the given tower
+    pub fn apply_effect(&mut self, effect: StatusEffect, tower: Handle) {
+        self.statuses.apply(effect, tower);
+    }
+    // Returns false if damage of the given type does nothing to the enemy. Sharp damage can't
+    // hurt lead or frozen bloons.
+    pub fn can_be_hurt_by(&self, damage_type: DamageType) -> bool {
+        return !(damage_type == DamageType::Sharp && (self.modifiers.lead || self.statuses.frozen()));
+    }
+    // Moves the enemy along the track at its speed times the pace, with its effects applied,
+    // counts down its effects and grows back a layer if it regrows. Returns the burn damage taken
+    // this step, with the tower that caused each burn.
+    pub fn update(&mut self, dt: f32, pace: f32, track: &Track, bloons: &BloonTypes) -> Vec<(u32, Handle)> {
+        if self.modifiers.regrow {
+            self.regrow_time += dt;
+            if self.regrow_time >= REGROW_TIME {
+                match bloons.parent_towards(self.origin, self.kind) {
+                    Some(parent) => self.set_kind(parent, bloons),
+                    None => self.regrow_time = 0.0,
+                }
+            }
+        }
+        let speed: f32 = self.speed * pace * self.statuses.speed_factor();
+        self.place(self.distance + speed * dt, track);
+        return self.statuses.update(dt);
+    }
+}
@@ -0,0 +1,48 @@
+pub const PI: f32 = std::f32::consts::PI;
+
+// Returns the squared distance between two points
+pub fn pythag(a: (f32,f32), b: (f32,f32)) -> f32 {
+    let x = a.0 - b.0;
+    let y = a.1 - b.1;
+    return x * x + y * y;
+}
+
+// Returns the distance between two points
+pub fn pythag_sqrt(a: (f32,f32), b: (f32,f32)) -> f32 {
+    let x = a.0 - b.0;
+    let y = a.1 - b.1;
+    return (x * x + y * y).sqrt();
+}
+
+pub fn angle_between(a: (f32,f32), b: (f32,f32)) -> f32 {
+    let (x,y) = (b.0-a.0, b.1-a.1);
+    let (ax,ay) = (x.abs(),y.abs());
+    
+    if x > 0.0 && y >= 0.0 {
+        return (ay/ax).atan();
+    } else if x <= 0.0 && y > 0.0 {
+        return (ax/ay).atan() + PI / 2.0;
+    } else if x < 0.0 && y <= 0.0 {
+        return (ay/ax).atan() + PI;
+    } else {
+        return (ax/ay).atan() + PI * 3.0 / 2.0;
+    }
+}
+
+// Rotates a point about a centre by the given angle in radians
+pub fn rotate_about(point: (f32,f32), centre: (f32,f32), angle: f32) -> (f32,f32) {
+    let (x,y) = (point.0 - centre.0, point.1 - centre.1);
+    let (sin_angle, cos_angle) = angle.sin_cos();
+    return (centre.0 + x * cos_angle - y * sin_angle, centre.1 + x * sin_angle + y * cos_angle);
+}
+
+// Returns the distance from a point to the nearest point on the line segment from a to b
+pub fn distance_to_segment(point: (f32,f32), a: (f32,f32), b: (f32,f32)) -> f32 {
+    let (dx,dy) = (b.0 - a.0, b.1 - a.1);
+    let length: f32 = dx * dx + dy * dy;
+    if length <= 0.0 {
+        return pythag_sqrt(point, a);
+    }
+    let t: f32 = (((point.0 - a.0) * dx + (point.1 - a.1) * dy) / length).clamp(0.0, 1.0);
+    return pythag_sqrt(point, (a.0 + dx * t, a.1 + dy * t));
+}
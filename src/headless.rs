@@ -0,0 +1,19 @@
+use crate::data::GameData;
+use crate::difficulty::Difficulty;
+use crate::simulation::Simulation;
+use crate::targeting::Targeting;
+
+// Runs a game with no window for the given number of steps and prints the final state. Each
+// round is started as soon as the last one ends.
+pub fn run(steps: u32, data: GameData, difficulty: Difficulty, seed: u64) {
+    let mut sim: Simulation = Simulation::new(difficulty,data,Targeting::First,seed);
+    for _ in 0..steps {
+        sim.start_round();
+        sim.step();
+    }
+    println!("steps: {}, seed: {}", sim.tick, sim.rng.seed());
+    println!("difficulty: {}", sim.difficulty.name());
+    println!("state: {:?}, health: {}, money: {}, rounds: {}/{}", sim.state, sim.health, sim.money, sim.round, sim.total_rounds());
+    println!("popped: {}, leaked: {}", sim.popped, sim.leaked);
+    println!("spawned: {}, enemies: {}, towers: {}, projectiles: {}", sim.spawn_count, sim.enemies.len(), sim.towers.len(), sim.projectiles.len());
+}
@@ -0,0 +1,61 @@
+use std::fmt;
+
+use crate::arena::Arena;
+use crate::geometry::pythag_sqrt;
+use crate::tower::Tower;
+use crate::track::{Ground, Map, TRACK_WIDTH};
+
+// The reason a tower can't be placed somewhere
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PlacementError {
+    // Part of the tower would be outside the map
+    OffMap,
+    // The tower would cover the track
+    OnTrack,
+    // The tower would overlap another tower
+    Overlapping,
+    // The tower would be on blocked ground
+    Blocked,
+    // A land tower would be on water, or a water tower on land
+    WrongGround,
+    // The player doesn't have enough money for the tower
+    CannotAfford,
+}
+
+impl fmt::Display for PlacementError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlacementError::OffMap => return write!(f, "Towers must be placed inside the map"),
+            PlacementError::OnTrack => return write!(f, "Towers can't be placed on the track"),
+            PlacementError::Overlapping => return write!(f, "Towers can't overlap other towers"),
+            PlacementError::Blocked => return write!(f, "Towers can't be placed there"),
+            PlacementError::WrongGround => return write!(f, "That tower can't be placed on that ground"),
+            PlacementError::CannotAfford => return write!(f, "Not enough money for that tower"),
+        }
+    }
+}
+
+// Checks that a tower of the given radius fits at the given position. Water towers must be
+// entirely on water and other towers entirely off it.
+pub fn check_placement(pos: (f32,f32), radius: f32, water: bool, map: &Map, towers: &Arena<Tower>) -> Result<(), PlacementError> {
+    let (width, height) = map.size;
+    if pos.0 - radius < 0.0 || pos.1 - radius < 0.0 || pos.0 + radius > width || pos.1 + radius > height {
+        return Err(PlacementError::OffMap);
+    }
+    if map.track.distance_to(pos) < radius + TRACK_WIDTH / 2.0 {
+        return Err(PlacementError::OnTrack);
+    }
+    let ground: Vec<Option<Ground>> = map.ground_under(pos, radius);
+    if ground.contains(&Some(Ground::Blocked)) {
+        return Err(PlacementError::Blocked);
+    }
+    if ground.iter().any(|&cell| (cell == Some(Ground::Water)) != water) {
+        return Err(PlacementError::WrongGround);
+    }
+    for tower in towers.values() {
+        if pythag_sqrt(pos, tower.get_pos()) < radius + tower.radius {
+            return Err(PlacementError::Overlapping);
+        }
+    }
+    return Ok(());
+}
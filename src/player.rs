@@ -0,0 +1,409 @@
+use macroquad::prelude::*;
+
+use crate::arena::Handle;
+use crate::command::Command;
+use crate::difficulty::Difficulty;
+use crate::render::{draw_ghost, draw_results, draw_simulation};
+use crate::replay::{Playback, Recorder, ReplayFile};
+use crate::save::{load_game, save_game};
+use crate::simulation::{SimEvent, Simulation};
+use crate::tower_type::TowerId;
+use crate::ui::{build_button, build_panel, difficulty_button, draw_build_panel, draw_difficulty_menu, draw_hud, draw_pause_menu, draw_speed_button, draw_tooltip, draw_tower_panel, next_round_button, pause_menu_button, sell_button, speed_button, tower_panel, under_ui, upgrade_button, HUD_HEIGHT, PAUSE_MENU};
+
+// How long a message stays on screen, in seconds
+const MESSAGE_TIME: f64 = 2.0;
+
+// The keys that choose which type of tower to build, in the order the types are listed
+const BUILD_KEYS: [KeyCode; 9] = [
+    KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5,
+    KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+];
+
+// The fastest the game can be run, in simulation steps for every step of real time
+const MAX_SPEED: u32 = 3;
+
+// The keys that buy the next upgrade on each path of the selected tower, and how they are shown
+const UPGRADE_KEYS: [(KeyCode, &str); 3] = [(KeyCode::Comma, "[,]"), (KeyCode::Period, "[.]"), (KeyCode::Slash, "[/]")];
+
+// Turns mouse and keyboard input into actions on the simulation, and draws it
+pub struct Player {
+    pub sim: Simulation,
+    // The type of tower being placed, if the player has picked one to build
+    build: Option<TowerId>,
+    // The tower whose upgrades are being shown
+    selected: Option<Handle>,
+    mouse_state: bool,
+    right_mouse_state: bool,
+    // The last message to show the player, its colour and the time it was shown
+    message: Option<(String, Color, f64)>,
+    // Whether the pause menu is open, which stops the simulation
+    paused: bool,
+    // Whether the difficulty menu is open, which is shown before a new game starts
+    choosing: bool,
+    // How many simulation steps are run for every step of real time
+    speed: u32,
+    // Whether the game is stopped by the speed controls, which leaves the game on screen so it
+    // can be stepped through one tick at a time
+    halted: bool,
+    // The file the game is saved to and loaded from
+    save_path: String,
+    // Records every command carried out, to be written to the replay file
+    recorder: Recorder,
+    replay_path: String,
+    // The replay being watched, if there is one. The player can't change the game while
+    // watching.
+    playback: Option<Playback>,
+}
+
+impl Player {
+    pub fn new(sim: Simulation, save_path: String, replay_path: String) -> Player {
+        let recorder: Recorder = Recorder::new(&sim);
+        return Player {
+            sim: sim,
+            build: None,
+            selected: None,
+            mouse_state: false,
+            right_mouse_state: false,
+            message: None,
+            paused: false,
+            choosing: false,
+            speed: 1,
+            halted: false,
+            save_path: save_path,
+            recorder: recorder,
+            replay_path: replay_path,
+            playback: None,
+        }
+    }
+    // Opens the difficulty menu, which has to be closed before the game can start
+    pub fn choose_difficulty(&mut self) {
+        self.choosing = true;
+    }
+    // Starts the game on the given difficulty. The recording restarts so replays start on the
+    // right difficulty too.
+    fn pick_difficulty(&mut self, difficulty: Difficulty) {
+        if self.sim.set_difficulty(difficulty) {
+            self.recorder = Recorder::new(&self.sim);
+        }
+        self.choosing = false;
+    }
+    // Handles input while the difficulty menu is open: clicking a difficulty or pressing its
+    // number picks it, and F9 loads a saved game instead
+    fn difficulty_input(&mut self) {
+        if is_key_pressed(KeyCode::F9) {
+            self.load();
+        }
+        for (i, difficulty) in Difficulty::ALL.into_iter().enumerate() {
+            let clicked: bool = is_mouse_button_pressed(MouseButton::Left) && difficulty_button(i).contains(mouse_position().into());
+            if clicked || BUILD_KEYS.get(i).is_some_and(|key| is_key_pressed(*key)) {
+                self.pick_difficulty(difficulty);
+            }
+        }
+        self.mouse_state = is_mouse_button_down(MouseButton::Left);
+    }
+    // Plays back a replay that has already been loaded into the simulation
+    pub fn watch(&mut self, replay: &ReplayFile) {
+        self.playback = Some(Playback::new(replay));
+        self.recorder = Recorder::new(&self.sim);
+    }
+    // Carries out a command and records it, or tells the player why it couldn't be done.
+    // Returns true if it was carried out.
+    fn perform(&mut self, command: Command) -> bool {
+        if self.playback.is_some() {
+            self.show_message(String::from("Watching a replay"), GRAY);
+            return false;
+        }
+        match self.sim.execute(command) {
+            Ok(()) => {
+                self.recorder.record(self.sim.tick, command);
+                return true;
+            }
+            Err(e) => {
+                self.show_message(e, RED);
+                return false;
+            }
+        }
+    }
+    // Shows a message to the player for a couple of seconds
+    fn show_message(&mut self, message: String, colour: Color) {
+        self.message = Some((message, colour, get_time()));
+    }
+    fn save(&mut self) {
+        match save_game(&self.sim, &self.save_path) {
+            Ok(()) => self.show_message(format!("Saved to {}", self.save_path), GREEN),
+            Err(e) => self.show_message(format!("Couldn't save: {}", e), RED),
+        }
+    }
+    fn load(&mut self) {
+        match load_game(&mut self.sim, &self.save_path) {
+            Ok(()) => {
+                self.selected = None;
+                self.choosing = false;
+                self.playback = None;
+                self.recorder = Recorder::new(&self.sim);
+                self.show_message(format!("Loaded {}", self.save_path), GREEN);
+            }
+            Err(e) => self.show_message(format!("Couldn't load: {}", e), RED),
+        }
+    }
+    fn save_replay(&mut self) {
+        // The recorder is empty while watching, so saving would write a replay of nothing
+        if self.playback.is_some() {
+            self.show_message(String::from("Watching a replay"), GRAY);
+            return;
+        }
+        match self.recorder.write(&self.sim, &self.replay_path) {
+            Ok(()) => self.show_message(format!("Replay saved to {}", self.replay_path), GREEN),
+            Err(e) => self.show_message(format!("Couldn't save replay: {}", e), RED),
+        }
+    }
+    // Runs the simulation for the given number of steps of real time, unless the game is paused.
+    // Faster speeds run more fixed steps rather than longer ones, so nothing moves far enough in
+    // one step to skip past what it should hit.
+    pub fn update(&mut self, steps: u32) {
+        if self.paused || self.halted || self.choosing {
+            return;
+        }
+        self.run(steps * self.speed);
+    }
+    // Runs the given number of fixed simulation steps, then tells the player what happened. When
+    // watching a replay, its commands are carried out on the steps they were recorded on, and the
+    // game stops where the recording ended.
+    fn run(&mut self, steps: u32) {
+        for _ in 0..steps {
+            if let Some(playback) = &mut self.playback {
+                playback.apply(&mut self.sim);
+                if playback.finished(&self.sim) {
+                    break;
+                }
+            }
+            self.sim.step();
+        }
+        for event in self.sim.take_events() {
+            match event {
+                SimEvent::RoundStarted(round) => self.show_message(format!("Round {} started", round), WHITE),
+                SimEvent::RoundEnded(round, bonus) => self.show_message(format!("Round {} cleared, +{} bonus", round, bonus), GREEN),
+            }
+        }
+    }
+    // Starts placing a tower of the given type, or stops if it was already being placed
+    fn pick_build(&mut self, kind: TowerId) {
+        if self.build == Some(kind) {
+            self.build = None;
+            return;
+        }
+        self.build = Some(kind);
+        self.selected = None;
+    }
+    // Handles a left click. Clicks on the build panel pick a tower to place, and the next click
+    // on the map places it, staying in build mode while shift is held. With a tower selected,
+    // clicks on its panel buy upgrades and clicks anywhere else select whatever tower is there
+    // instead. Otherwise clicking a tower selects it.
+    fn click(&mut self, mx: f32, my: f32) {
+        let mouse: Vec2 = Vec2::new(mx,my);
+        if self.paused {
+            for button in 0..PAUSE_MENU.len() {
+                if pause_menu_button(button).contains(mouse) {
+                    match button {
+                        0 => self.paused = false,
+                        1 => self.save(),
+                        2 => self.load(),
+                        _ => self.save_replay(),
+                    }
+                }
+            }
+            return;
+        }
+        if next_round_button().contains(mouse) {
+            self.perform(Command::StartRound);
+            return;
+        }
+        if speed_button().contains(mouse) {
+            self.cycle_speed();
+            return;
+        }
+        let count: usize = self.sim.tower_types.len();
+        for kind in (0..count).map(TowerId) {
+            if build_button(kind, count).contains(mouse) {
+                self.pick_build(kind);
+                return;
+            }
+        }
+        if let Some(kind) = self.build {
+            if build_panel().contains(mouse) {
+                return;
+            }
+            if under_ui((mx,my), self.sim.tower_types.get(kind).radius) {
+                self.show_message(String::from("Towers can't be placed under the menus"), RED);
+                return;
+            }
+            let placed: bool = self.perform(Command::PlaceTower { kind: kind, x: mx, y: my });
+            if placed && !is_key_down(KeyCode::LeftShift) && !is_key_down(KeyCode::RightShift) {
+                self.build = None;
+            }
+            return;
+        }
+        if let Some(tower) = self.selected {
+            let paths: usize = self.sim.towers.get(tower).map_or(0, |tower| tower.tiers.len());
+            if !tower_panel(paths).contains(mouse) {
+                self.selected = self.sim.tower_at(mx,my);
+                return;
+            }
+            for path in 0..paths {
+                if upgrade_button(path).contains(mouse) {
+                    self.perform(Command::UpgradeTower { tower: tower, path: path });
+                }
+            }
+            if sell_button(paths).contains(mouse) {
+                self.sell_selected();
+            }
+            return;
+        }
+        self.selected = self.sim.tower_at(mx,my);
+    }
+    // Sells the selected tower
+    fn sell_selected(&mut self) {
+        let Some(tower) = self.selected else {
+            return;
+        };
+        let refund: u32 = self.sim.towers.get(tower).map_or(0, |tower| self.sim.sell_value(tower));
+        if self.perform(Command::SellTower { tower: tower }) {
+            self.selected = None;
+            self.show_message(format!("Sold for ${}", refund), GREEN);
+        }
+    }
+    // Switches to the next speed, going back to normal speed after the fastest
+    fn cycle_speed(&mut self) {
+        self.speed = self.speed % MAX_SPEED + 1;
+        self.halted = false;
+    }
+    // Handles the speed controls: P stops and starts the game, F cycles through the speeds and
+    // while stopped N runs a single step
+    fn speed_input(&mut self) {
+        if is_key_pressed(KeyCode::P) {
+            self.halted = !self.halted;
+        }
+        if is_key_pressed(KeyCode::F) {
+            self.cycle_speed();
+        }
+        if is_key_pressed(KeyCode::N) && self.halted {
+            self.run(1);
+        }
+    }
+    pub fn input(&mut self) {
+        if self.choosing {
+            self.difficulty_input();
+            return;
+        }
+        // Escape cancels placing a tower or deselects the selected tower before it opens the pause
+        // menu
+        if is_key_pressed(KeyCode::Escape) {
+            if self.build.is_some() && !self.paused {
+                self.build = None;
+            } else if self.selected.is_some() && !self.paused {
+                self.selected = None;
+            } else {
+                self.paused = !self.paused;
+            }
+        }
+        if is_key_pressed(KeyCode::F5) {
+            self.save();
+        }
+        if is_key_pressed(KeyCode::F9) {
+            self.load();
+        }
+        if is_key_pressed(KeyCode::F6) {
+            self.save_replay();
+        }
+        if is_key_pressed(KeyCode::Space) && !self.paused {
+            self.perform(Command::StartRound);
+        }
+        if !self.paused {
+            self.speed_input();
+        }
+        if let (Some(tower), false) = (self.selected, self.paused) {
+            if is_key_pressed(KeyCode::Delete) || is_key_pressed(KeyCode::Backspace) {
+                self.sell_selected();
+            } else if is_key_pressed(KeyCode::Tab) {
+                self.perform(Command::CycleTargeting { tower: tower });
+            }
+            let paths: usize = self.sim.towers.get(tower).map_or(0, |tower| tower.tiers.len());
+            for (path, (key, _)) in UPGRADE_KEYS.iter().enumerate().take(paths) {
+                if is_key_pressed(*key) {
+                    self.perform(Command::UpgradeTower { tower: tower, path: path });
+                }
+            }
+        }
+        for (i, key) in BUILD_KEYS.iter().enumerate() {
+            if is_key_pressed(*key) && i < self.sim.tower_types.len() && !self.paused {
+                self.pick_build(TowerId(i));
+            }
+        }
+
+        if is_mouse_button_down(MouseButton::Left) {
+            if !self.mouse_state {
+                let (mx,my) = mouse_position();
+                self.click(mx,my);
+            }
+            self.mouse_state = true;
+        }
+        else {
+            self.mouse_state = false;
+        }
+
+        // Right clicking cancels placing a tower, or switches the tower clicked to its next
+        // targetting priority
+        if is_mouse_button_down(MouseButton::Right) {
+            if !self.right_mouse_state && !self.paused {
+                let (mx,my) = mouse_position();
+                if self.build.is_some() {
+                    self.build = None;
+                } else if let Some(tower) = self.sim.tower_at(mx,my) {
+                    self.perform(Command::CycleTargeting { tower: tower });
+                }
+            }
+            self.right_mouse_state = true;
+        }
+        else {
+            self.right_mouse_state = false;
+        }
+    }
+    pub fn draw(&self) {
+        draw_simulation(&self.sim);
+        if !self.sim.in_round && self.sim.round < self.sim.total_rounds() {
+            let button: Rect = next_round_button();
+            draw_rectangle(button.x, button.y, button.w, button.h, DARKGREEN);
+            draw_text("Next Round", button.x + 12.0, button.y + 27.0, 28.0, WHITE);
+        }
+        draw_build_panel(&self.sim, self.build);
+        if let Some(tower) = self.selected {
+            let keys: Vec<&str> = UPGRADE_KEYS.iter().map(|(_, name)| *name).collect();
+            draw_tower_panel(&self.sim, tower, &keys);
+        }
+        let (mx,my) = mouse_position();
+        let over_panel: bool = self.selected.is_some_and(|tower| {
+            let paths: usize = self.sim.towers.get(tower).map_or(0, |tower| tower.tiers.len());
+            return tower_panel(paths).contains(Vec2::new(mx,my));
+        });
+        if let Some(kind) = self.build {
+            let allowed: bool = !under_ui((mx,my), self.sim.tower_types.get(kind).radius);
+            draw_ghost(&self.sim, kind, (mx,my), allowed);
+        } else if !self.paused && !over_panel && my > HUD_HEIGHT {
+            draw_tooltip(&self.sim, (mx,my));
+        }
+        draw_speed_button(self.speed, self.halted);
+        draw_hud(&self.sim, self.speed, self.paused || self.halted);
+        draw_results(&self.sim);
+        if self.paused {
+            draw_pause_menu();
+        }
+        if self.choosing {
+            draw_difficulty_menu(self.sim.rounds.len());
+        }
+        if let Some((message, colour, time)) = &self.message {
+            if get_time() - time < MESSAGE_TIME {
+                draw_text(message, 10.0, screen_height() - 70.0, 24.0, *colour);
+            }
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+
+use crate::arena::Handle;
+use crate::geometry::{angle_between, pythag_sqrt, PI};
+use crate::status::StatusEffect;
+use crate::tower_type::{DamageType, PathKind, ProjectileStats};
+
+// The path a projectile follows, along with whatever it needs to remember between steps
+#[derive(Clone, Serialize, Deserialize)]
+enum Projectilepath {
+    Straight { angle: f32, source: (f32,f32) },
+    // The direction the projectile is heading in, which turns towards its target each step
+    Homing { angle: f32, turn_rate: f32 },
+    // Loops out and back to the source, taking the projectile's whole lifetime
+    Boomerang { angle: f32, source: (f32,f32), lifetime: f32 },
+    // Flies from the source to the target over the given number of seconds, at most the given
+    // height off the ground
+    Arc { source: (f32,f32), target: (f32,f32), height: f32, flight: f32 },
+    Orbit { angle: f32, centre: (f32,f32), radius: f32 },
+}
+
+impl Projectilepath {
+    fn projectile_straight (source: (f32,f32), target: (f32,f32),) -> Projectilepath {
+        return Projectilepath::Straight {
+            angle: angle_between(source, target),
+            source: source,
+        }
+    }
+    // Circles the centre at the given radius, starting on the side facing the target
+    fn projectile_circle (centre: (f32,f32), target: (f32,f32), radius: f32) -> Projectilepath {
+        return Projectilepath::Orbit {
+            angle: angle_between(centre, target),
+            centre: centre,
+            radius: radius,
+        }
+    }
+    fn projectile_homing (source: (f32,f32), target: (f32,f32), turn_rate: f32) -> Projectilepath {
+        return Projectilepath::Homing {
+            angle: angle_between(source, target),
+            turn_rate: turn_rate,
+        }
+    }
+    fn projectile_boomerang (source: (f32,f32), target: (f32,f32), lifetime: f32) -> Projectilepath {
+        return Projectilepath::Boomerang {
+            angle: angle_between(source, target),
+            source: source,
+            lifetime: lifetime,
+        }
+    }
+    fn projectile_arc (source: (f32,f32), target: (f32,f32), height: f32, speed: f32) -> Projectilepath {
+        let flight: f32 = if speed > 0.0 { pythag_sqrt(source, target) / speed } else { 0.0 };
+        return Projectilepath::Arc {
+            source: source,
+            target: target,
+            height: height,
+            flight: flight,
+        }
+    }
+
+    // Returns the position of the projectile after the given time alive. Homing projectiles
+    // turn towards the target position, if their target is still around, and otherwise carry
+    // on the way they were going.
+    fn update(&mut self, pos: (f32,f32), speed: f32, time: f32, dt: f32, target: Option<(f32,f32)>) -> (f32,f32) {
+        match self {
+            Projectilepath::Straight { angle, source } => {
+                let distance: f32 = speed * time;
+                return (source.0 + distance * angle.cos(), source.1 + distance * angle.sin());
+            }
+            Projectilepath::Homing { angle, turn_rate } => {
+                if let Some(target) = target {
+                    // The turn needed, wrapped into -PI to PI so the projectile turns the short way
+                    let turn: f32 = (angle_between(pos, target) - *angle + PI).rem_euclid(2.0 * PI) - PI;
+                    let max_turn: f32 = *turn_rate * dt;
+                    *angle += turn.clamp(-max_turn, max_turn);
+                }
+                let distance: f32 = speed * dt;
+                return (pos.0 + distance * angle.cos(), pos.1 + distance * angle.sin());
+            }
+            Projectilepath::Boomerang { angle, source, lifetime } => {
+                let t: f32 = if *lifetime > 0.0 { (time / *lifetime).min(1.0) } else { 1.0 };
+                // Roughly the distance covered at the projectile's speed over the loop
+                let reach: f32 = speed * *lifetime / PI;
+                let forward: f32 = reach * (PI * t).sin();
+                let side: f32 = reach * 0.5 * (2.0 * PI * t).sin();
+                let (sin_angle, cos_angle) = angle.sin_cos();
+                return (source.0 + forward * cos_angle - side * sin_angle, source.1 + forward * sin_angle + side * cos_angle);
+            }
+            Projectilepath::Arc { source, target, flight, .. } => {
+                let t: f32 = if *flight > 0.0 { (time / *flight).min(1.0) } else { 1.0 };
+                return (source.0 + (target.0 - source.0) * t, source.1 + (target.1 - source.1) * t);
+            }
+            Projectilepath::Orbit { angle, centre, radius } => {
+                let turned: f32 = if *radius > 0.0 { *angle + time * speed / *radius } else { *angle };
+                return (centre.0 + *radius * turned.cos(), centre.1 + *radius * turned.sin());
+            }
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Projectile {
+    pub x: f32,
+    pub y: f32,
+    lifetime: f32,
+    time: f32,
+    speed: f32,
+    path: Projectilepath,
+    // The enemy the projectile was fired at, which homing projectiles follow
+    pub target: Option<Handle>,
+    // The tower that fired the projectile, which is credited with what it pops
+    pub tower: Handle,
+    pub pierce: u32,
+    // The enemies the projectile has already hit, which it passes through rather than hitting
+    // again. Popped enemies keep their handle for their first child, so the child is passed
+    // through too.
+    pub hit: Vec<Handle>,
+    pub damage: u32,
+    pub radius: f32,
+    pub splash: f32,
+    pub damage_type: DamageType,
+    // Applied to every enemy the projectile hits
+    pub effect: Option<StatusEffect>,
+}
+
+impl Projectile {
+    pub fn new(source: (f32,f32), target: (f32,f32), target_enemy: Option<Handle>, tower: Handle, stats: &ProjectileStats) -> Projectile {
+        let mut lifetime: f32 = stats.lifetime;
+        let projectile_path: Projectilepath = match stats.path {
+            PathKind::Straight => Projectilepath::projectile_straight(source, target),
+            PathKind::Homing { turn_rate } => Projectilepath::projectile_homing(source, target, turn_rate),
+            PathKind::Boomerang => Projectilepath::projectile_boomerang(source, target, stats.lifetime),
+            PathKind::Orbit { radius } => Projectilepath::projectile_circle(source, target, radius),
+            PathKind::Arc { height } => {
+                let path: Projectilepath = Projectilepath::projectile_arc(source, target, height, stats.speed);
+                // An arcing projectile's lifetime only starts counting once it lands
+                if let Projectilepath::Arc { flight, .. } = path {
+                    lifetime += flight;
+                }
+                path
+            }
+        };
+        return Projectile {
+            x: source.0,
+            y: source.1,
+            lifetime: lifetime,
+            time: 0.0,
+            speed: stats.speed,
+            path: projectile_path,
+            target: target_enemy,
+            tower: tower,
+            pierce: stats.pierce,
+            hit: vec![],
+            damage: stats.damage,
+            radius: stats.radius,
+            splash: stats.splash,
+            damage_type: stats.damage_type,
+            effect: stats.effect,
+        }
+    }
+    // Returns true once an arcing projectile has come down. Other projectiles never leave the
+    // ground.
+    pub fn landed(&self) -> bool {
+        if let Projectilepath::Arc { flight, .. } = self.path {
+            return self.time >= flight;
+        }
+        return false;
+    }
+    // Returns false while the projectile is in the air and can't hit anything
+    pub fn can_hit(&self) -> bool {
+        if let Projectilepath::Arc { .. } = self.path {
+            return self.landed();
+        }
+        return true;
+    }
+    // Returns how far above the ground the projectile is, for drawing
+    pub fn height(&self) -> f32 {
+        if let Projectilepath::Arc { height, flight, .. } = self.path {
+            if flight > 0.0 && self.time < flight {
+                let t: f32 = self.time / flight;
+                return 4.0 * height * t * (1.0 - t);
+            }
+        }
+        return 0.0;
+    }
+    // Moves the projectile along its path, returning true once its lifetime has elapsed. The
+    // target position is where the enemy it was fired at is now, if it is still around.
+    pub fn update(&mut self, dt: f32, target: Option<(f32,f32)>) -> bool {
+        self.time += dt;
+        let (nx,ny) = self.path.update((self.x,self.y), self.speed, self.time, dt, target);
+        (self.x,self.y) = (nx,ny);
+        if self.time >= self.lifetime {
+            return true;
+        }
+        return false;
+    }
+}
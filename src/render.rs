@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use macroquad::prelude::*;
+
+use crate::bloon::Modifiers;
+use crate::clock::TICK_RATE;
+use crate::enemy::Enemy;
+use crate::simulation::{GameState, Simulation};
+use crate::status::Statuses;
+use crate::tower_type::{Attack, TowerId};
+use crate::track::{Ground, AREA_CELL, TRACK_WIDTH};
+
+struct Tri {
+    pos1: Vec2,
+    pos2: Vec2,
+    pos3: Vec2,
+    colour: Color,
+}
+
+impl Tri {
+    fn new(x: f32, y: f32, colour: Color) -> Tri {
+        return Tri {
+            pos1: Vec2::new(x-10.0,y-10.0),
+            pos2: Vec2::new(x+10.0,y-10.0),
+            pos3: Vec2::new(x,y+10.0),
+            colour: colour,
+        }
+    }
+    fn draw(&self) {
+        draw_triangle(self.pos1,self.pos2,self.pos3,self.colour);
+    }
+}
+
+// Converts a (red, green, blue) colour from the game data into a macroquad colour
+fn colour(rgb: (u8,u8,u8)) -> Color {
+    return Color::from_rgba(rgb.0, rgb.1, rgb.2, 255);
+}
+
+// Draws the track, then the enemies, towers and projectiles of the simulation. The HUD and the
+// results are drawn over the top separately.
+pub fn draw_simulation(sim: &Simulation) {
+    draw_areas(&sim.map.areas);
+    for pair in sim.map.track.points().windows(2) {
+        draw_line(pair[0].0, pair[0].1, pair[1].0, pair[1].1, TRACK_WIDTH, DARKGRAY);
+    }
+    for i in sim.enemies.values() {
+        let mut body: Color = colour(i.colour);
+        if i.modifiers.camo {
+            body.a = 0.45;
+        }
+        Tri::new(i.x,i.y,body).draw();
+        draw_modifiers(i);
+        draw_statuses(i);
+    }
+    for i in sim.towers.values() {
+        if let Attack::Aura = i.stats.attack {
+            draw_circle(i.x, i.y, i.stats.range, Color::new(0.6, 0.85, 1.0, 0.15));
+        }
+        Tri::new(i.x,i.y,colour(sim.tower_types.get(i.kind).colour)).draw();
+        draw_text(i.targeting.name(), i.x - 15.0, i.y + 24.0, 16.0, WHITE);
+    }
+    for i in sim.projectiles.values() {
+        Tri::new(i.x,i.y - i.height(),YELLOW).draw();
+    }
+}
+
+// Draws the blocked and water cells of a map
+pub fn draw_areas(areas: &HashMap<(i32,i32), Ground>) {
+    for (&(x,y), &ground) in areas {
+        let colour: Color = match ground {
+            Ground::Blocked => Color::from_rgba(90, 60, 40, 255),
+            Ground::Water => Color::from_rgba(40, 90, 170, 255),
+        };
+        draw_rectangle(x as f32 * AREA_CELL, y as f32 * AREA_CELL, AREA_CELL, AREA_CELL, colour);
+    }
+}
+
+// Draws a see-through tower of the given type at the mouse with its range, unless it can reach
+// the whole map, to show where it would be placed. It turns red where it can't be placed, as given, or the player can't
+// afford it.
+pub fn draw_ghost(sim: &Simulation, kind: TowerId, pos: (f32,f32), allowed: bool) {
+    let tower_type = sim.tower_types.get(kind);
+    let mut body: Color = if allowed && sim.can_place(kind, pos.0, pos.1).is_ok() { colour(tower_type.colour) } else { RED };
+    body.a = 0.5;
+    if !tower_type.stats.global {
+        draw_circle(pos.0, pos.1, tower_type.stats.range, Color::new(body.r, body.g, body.b, 0.12));
+        draw_circle_lines(pos.0, pos.1, tower_type.stats.range, 1.5, body);
+    }
+    draw_circle_lines(pos.0, pos.1, tower_type.radius, 1.0, body);
+    Tri::new(pos.0,pos.1,body).draw();
+}
+
+// Marks the modifiers of an enemy: camo bloons are see-through with a green ring, lead bloons
+// have a grey casing, fortified bloons a brown frame and regrow bloons a pink plus
+fn draw_modifiers(enemy: &Enemy) {
+    let modifiers: Modifiers = enemy.modifiers;
+    if modifiers.camo {
+        draw_circle_lines(enemy.x, enemy.y, 15.0, 1.0, DARKGREEN);
+    }
+    if modifiers.lead {
+        draw_circle_lines(enemy.x, enemy.y, 11.0, 3.0, GRAY);
+    }
+    if modifiers.fortified {
+        draw_rectangle_lines(enemy.x - 12.0, enemy.y - 12.0, 24.0, 24.0, 3.0, BROWN);
+    }
+    if modifiers.regrow {
+        draw_text("+", enemy.x + 8.0, enemy.y - 4.0, 20.0, PINK);
+    }
+}
+
+// Marks the effects on an enemy: a blue ring when slowed, a sheet of ice when frozen, an orange
+// flame when burning and stars above it when stunned
+fn draw_statuses(enemy: &Enemy) {
+    let statuses: &Statuses = &enemy.statuses;
+    if statuses.slowed() {
+        draw_circle_lines(enemy.x, enemy.y, 13.0, 2.0, SKYBLUE);
+    }
+    if statuses.frozen() {
+        draw_circle(enemy.x, enemy.y, 13.0, Color::new(0.75, 0.95, 1.0, 0.6));
+    }
+    if statuses.burning() {
+        draw_triangle(Vec2::new(enemy.x - 4.0, enemy.y - 12.0), Vec2::new(enemy.x + 4.0, enemy.y - 12.0), Vec2::new(enemy.x, enemy.y - 20.0), ORANGE);
+    }
+    if statuses.stunned() {
+        for offset in [-6.0, 0.0, 6.0] {
+            draw_circle(enemy.x + offset, enemy.y - 16.0, 2.0, YELLOW);
+        }
+    }
+}
+
+// Draws the end of game results over the top of the game, once it is over
+pub fn draw_results(sim: &Simulation) {
+    if sim.state == GameState::Playing {
+        return;
+    }
+    let (width, height) = (screen_width(), screen_height());
+    draw_rectangle(0.0, 0.0, width, height, Color::new(0.0, 0.0, 0.0, 0.75));
+    let title: &str = if sim.state == GameState::Won { "Victory" } else { "Game Over" };
+    let lines: [String; 5] = [
+        String::from(title),
+        format!("Rounds survived: {}", sim.round),
+        format!("Layers popped: {}", sim.popped),
+        format!("Layers leaked: {}", sim.leaked),
+        format!("Time: {:.0}s", sim.tick as f32 / TICK_RATE as f32),
+    ];
+    for (i, line) in lines.iter().enumerate() {
+        let size: f32 = if i == 0 { 48.0 } else { 28.0 };
+        let dims = measure_text(line, None, size as u16, 1.0);
+        draw_text(line, (width - dims.width) / 2.0, height / 3.0 + i as f32 * 40.0, size, WHITE);
+    }
+}
@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+
+use crate::command::Command;
+use crate::save::{SaveFile, SAVE_VERSION};
+use crate::simulation::Simulation;
+
+// The version of the replay format written by this build
+pub const REPLAY_VERSION: u32 = 1;
+
+// A recorded game: the state it started from, every command with the step it was carried out
+// on, and a hash of the state it ended in
+#[derive(Serialize, Deserialize)]
+pub struct ReplayFile {
+    pub version: u32,
+    pub start: SaveFile,
+    pub commands: Vec<(u64, Command)>,
+    pub end_tick: u64,
+    pub hash: u64,
+}
+
+// Returns a hash of the whole state of the game, for checking that a replay ended up in the
+// same place as the game it recorded. This is FNV-1a over the saved game, so it is the same on
+// every machine and build.
+pub fn state_hash(sim: &Simulation) -> u64 {
+    let text: String = ron::to_string(&sim.to_save()).unwrap_or_default();
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in text.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    return hash;
+}
+
+// Records the commands carried out in a game so it can be replayed
+pub struct Recorder {
+    start: SaveFile,
+    commands: Vec<(u64, Command)>,
+}
+
+impl Recorder {
+    // Starts recording from the game as it is now
+    pub fn new(sim: &Simulation) -> Recorder {
+        return Recorder {
+            start: sim.to_save(),
+            commands: vec![],
+        }
+    }
+
+    // Records a command carried out before the given step
+    pub fn record(&mut self, tick: u64, command: Command) {
+        self.commands.push((tick, command));
+    }
+
+    // Writes everything recorded so far to the given file, ending at the game as it is now
+    pub fn write(&self, sim: &Simulation, path: &str) -> Result<(), String> {
+        let replay: ReplayFile = ReplayFile {
+            version: REPLAY_VERSION,
+            start: self.start.clone(),
+            commands: self.commands.clone(),
+            end_tick: sim.tick,
+            hash: state_hash(sim),
+        };
+        let text: String = ron::ser::to_string_pretty(&replay, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())?;
+        return std::fs::write(path, text).map_err(|e| format!("{}: {}", path, e));
+    }
+}
+
+// Reads a replay and puts the game into the state it starts from. The replay must have been
+// recorded with this version and the same game data.
+pub fn load_replay(sim: &mut Simulation, path: &str) -> Result<ReplayFile, String> {
+    let text: String = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let replay: ReplayFile = ron::from_str(&text).map_err(|e| format!("{}: {}", path, e))?;
+    if replay.version != REPLAY_VERSION {
+        return Err(format!("{}: replay version {} can't be played, expected {}", path, replay.version, REPLAY_VERSION));
+    }
+    if replay.start.version != SAVE_VERSION {
+        return Err(format!("{}: replay starts from save version {}, expected {}", path, replay.start.version, SAVE_VERSION));
+    }
+    if replay.start.data != sim.data_ids() {
+        return Err(format!("{}: replay was recorded with different game data", path));
+    }
+    sim.restore(replay.start.clone());
+    return Ok(replay);
+}
+
+// Plays back the commands of a replay on their steps
+pub struct Playback {
+    commands: Vec<(u64, Command)>,
+    // The index of the next command to carry out
+    next: usize,
+    end_tick: u64,
+}
+
+impl Playback {
+    pub fn new(replay: &ReplayFile) -> Playback {
+        return Playback {
+            commands: replay.commands.clone(),
+            next: 0,
+            end_tick: replay.end_tick,
+        }
+    }
+
+    // Carries out every command due before the game's next step
+    pub fn apply(&mut self, sim: &mut Simulation) {
+        while let Some(&(tick, command)) = self.commands.get(self.next) {
+            if tick > sim.tick {
+                break;
+            }
+            // Commands that failed when recorded fail the same way again, so the result is ignored
+            let _ = sim.execute(command);
+            self.next += 1;
+        }
+    }
+
+    // Returns true once every step of the replay has been played
+    pub fn finished(&self, sim: &Simulation) -> bool {
+        return sim.tick >= self.end_tick;
+    }
+}
+
+// Plays a replay through to the end without a window and checks that it ends in the state it
+// was recorded ending in. Prints the result and returns true if the hashes match.
+pub fn verify(sim: &mut Simulation, replay: &ReplayFile) -> bool {
+    let mut playback: Playback = Playback::new(replay);
+    while !playback.finished(sim) {
+        playback.apply(sim);
+        let tick: u64 = sim.tick;
+        sim.step();
+        // The game stops stepping once it is over, so stop there too
+        if sim.tick == tick {
+            break;
+        }
+    }
+    playback.apply(sim);
+    let hash: u64 = state_hash(sim);
+    println!("steps: {}, commands: {}", sim.tick, replay.commands.len());
+    println!("expected hash: {:016x}, got: {:016x}", replay.hash, hash);
+    return hash == replay.hash;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::GameData;
+    use crate::difficulty::Difficulty;
+    use crate::targeting::Targeting;
+    use crate::tower_type::TowerId;
+
+    fn new_sim() -> Simulation {
+        let data: GameData = GameData::load(None, None, None, None).expect("the built in data loads");
+        return Simulation::new(Difficulty::Medium, data, Targeting::First, 7);
+    }
+
+    #[test]
+    fn replay_ends_where_recording_did() {
+        let mut sim: Simulation = new_sim();
+        let mut recorder: Recorder = Recorder::new(&sim);
+        for _ in 0..900 {
+            let command: Option<Command> = match sim.tick {
+                0 => Some(Command::PlaceTower { kind: TowerId(0), x: 200.0, y: 140.0 }),
+                1 => Some(Command::StartRound),
+                120 => Some(Command::PlaceTower { kind: TowerId(1), x: 400.0, y: 60.0 }),
+                240 => sim.towers.iter().next().map(|(tower, _)| Command::UpgradeTower { tower: tower, path: 0 }),
+                300 => sim.towers.iter().next().map(|(tower, _)| Command::CycleTargeting { tower: tower }),
+                _ => None,
+            };
+            // Only commands that were carried out are recorded, as when playing
+            if let Some(command) = command {
+                if sim.execute(command).is_ok() {
+                    recorder.record(sim.tick, command);
+                }
+            }
+            sim.step();
+        }
+        assert_eq!(recorder.commands.len(), 5);
+        assert!(sim.popped > 0);
+
+        let path: String = std::env::temp_dir().join("blons_replay_test.ron").to_string_lossy().into_owned();
+        recorder.write(&sim, &path).expect("the replay is written");
+        let mut played: Simulation = new_sim();
+        let replay: ReplayFile = load_replay(&mut played, &path).expect("the replay loads");
+        let _ = std::fs::remove_file(&path);
+        assert!(verify(&mut played, &replay));
+        assert_eq!(state_hash(&played), state_hash(&sim));
+    }
+}
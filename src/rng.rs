@@ -0,0 +1,39 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+// The single source of randomness for the simulation. Everything random in a game must be
+// drawn from here so that the same seed always plays out the same way.
+pub struct GameRng {
+    seed: u64,
+    rng: ChaCha8Rng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> GameRng {
+        return GameRng {
+            seed: seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+    // Returns an rng with the given seed that has already been used up to the given position,
+    // as saved by word_pos
+    pub fn restore(seed: u64, word_pos: u128) -> GameRng {
+        let mut rng: GameRng = GameRng::new(seed);
+        rng.rng.set_word_pos(word_pos);
+        return rng;
+    }
+    pub fn seed(&self) -> u64 {
+        return self.seed;
+    }
+    // Returns how far through its stream of numbers the rng is
+    pub fn word_pos(&self) -> u128 {
+        return self.rng.get_word_pos();
+    }
+    // Returns a value in [low, high), or low when the range is empty
+    pub fn range(&mut self, low: f32, high: f32) -> f32 {
+        if high <= low {
+            return low;
+        }
+        return self.rng.gen_range(low..high);
+    }
+}
@@ -0,0 +1,93 @@
+use serde::Deserialize;
+
+use crate::bloon::{BloonId, BloonTypes, Modifiers};
+use crate::clock::TICK_RATE;
+
+// The rounds shipped with the game, used when there is no rounds file next to the game
+pub const DEFAULT_ROUNDS: &str = include_str!("../assets/rounds.ron");
+
+// A spawn group as written in the rounds file, with its bloon given by id
+#[derive(Deserialize)]
+struct SpawnEntry {
+    bloon: String,
+    count: u32,
+    // Seconds between each bloon in the group
+    spacing: f32,
+    // Seconds from the start of the round to the first bloon in the group
+    delay: f32,
+    #[serde(default)]
+    modifiers: Modifiers,
+}
+
+pub struct SpawnGroup {
+    pub bloon: BloonId,
+    pub count: u32,
+    // Steps between each bloon in the group
+    pub spacing: u64,
+    // Steps from the start of the round to the first bloon in the group
+    pub delay: u64,
+    pub modifiers: Modifiers,
+}
+
+// A bloon to send in a round: the step of the round it is sent on, its type and its modifiers
+pub type Spawn = (u64, BloonId, Modifiers);
+
+pub struct Rounds {
+    rounds: Vec<Vec<SpawnGroup>>,
+}
+
+// Converts a time in seconds to the nearest whole number of simulation steps
+fn to_steps(seconds: f32) -> u64 {
+    return (seconds.max(0.0) * TICK_RATE as f32).round() as u64;
+}
+
+impl Rounds {
+    // Parses a list of rounds, checking that every bloon exists
+    pub fn parse(text: &str, bloons: &BloonTypes) -> Result<Rounds, String> {
+        let entries: Vec<Vec<SpawnEntry>> = ron::from_str(text).map_err(|e| e.to_string())?;
+        if entries.is_empty() {
+            return Err(String::from("there must be at least one round"));
+        }
+        let mut rounds: Vec<Vec<SpawnGroup>> = vec![];
+        for (number, round) in entries.iter().enumerate() {
+            let mut groups: Vec<SpawnGroup> = vec![];
+            for group in round {
+                let bloon: BloonId = match bloons.find(&group.bloon) {
+                    Some(bloon) => bloon,
+                    None => return Err(format!("round {} has unknown bloon '{}'", number + 1, group.bloon)),
+                };
+                groups.push(SpawnGroup {
+                    bloon: bloon,
+                    count: group.count,
+                    spacing: to_steps(group.spacing),
+                    delay: to_steps(group.delay),
+                    modifiers: group.modifiers,
+                });
+            }
+            rounds.push(groups);
+        }
+        return Ok(Rounds { rounds: rounds });
+    }
+
+    // Returns the number of rounds in a game
+    pub fn len(&self) -> u32 {
+        return self.rounds.len() as u32;
+    }
+
+    // Returns the bloons sent in the given round, latest first
+    pub fn schedule(&self, round: u32) -> Vec<Spawn> {
+        let mut schedule: Vec<Spawn> = vec![];
+        if let Some(groups) = self.rounds.get(round as usize) {
+            for group in groups {
+                for i in 0..group.count {
+                    schedule.push((group.delay + group.spacing * i as u64, group.bloon, group.modifiers));
+                }
+            }
+        }
+        // The sort is stable, so after reversing, bloons sent on the same step still come off the
+        // end of the list in the order they were written
+        schedule.sort_by_key(|&(step, _, _)| step);
+        schedule.reverse();
+        return schedule;
+    }
+}
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+
+use crate::arena::Arena;
+use crate::difficulty::Difficulty;
+use crate::enemy::Enemy;
+use crate::projectile::Projectile;
+use crate::round::Spawn;
+use crate::simulation::{GameState, Simulation};
+use crate::targeting::Targeting;
+use crate::tower::Tower;
+
+// The version of the save format written by this build. Saves with any other version are
+// refused rather than loaded wrongly.
+pub const SAVE_VERSION: u32 = 4;
+
+// The game data a save was made with. Bloons and towers are saved by their index in the data, so
+// a save can only be loaded with the same data. Enemies are saved by their distance along the
+// track, so the map's waypoints must match as well as its name.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct DataIds {
+    pub map: String,
+    pub track: Vec<(f32,f32)>,
+    pub bloons: Vec<String>,
+    pub towers: Vec<String>,
+    pub rounds: u32,
+}
+
+// Everything needed to carry on a game exactly where it was saved
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SaveFile {
+    pub version: u32,
+    pub data: DataIds,
+    pub state: GameState,
+    pub health: u32,
+    pub money: u32,
+    pub def_target: Targeting,
+    pub difficulty: Difficulty,
+    pub enemies: Arena<Enemy>,
+    pub projectiles: Arena<Projectile>,
+    pub towers: Arena<Tower>,
+    // The seed of the rng and how far through it the game had got
+    pub seed: u64,
+    pub word_pos: u128,
+    pub tick: u64,
+    pub spawn_count: u32,
+    pub round: u32,
+    pub in_round: bool,
+    pub round_tick: u64,
+    pub schedule: Vec<Spawn>,
+    pub popped: u32,
+    pub leaked: u32,
+}
+
+// Writes the game to the given file
+pub fn save_game(sim: &Simulation, path: &str) -> Result<(), String> {
+    let text: String = ron::ser::to_string_pretty(&sim.to_save(), ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())?;
+    return std::fs::write(path, text).map_err(|e| format!("{}: {}", path, e));
+}
+
+// Replaces the game with the one saved in the given file. The game is left as it was if the
+// save can't be read or was made with a different version or different game data.
+pub fn load_game(sim: &mut Simulation, path: &str) -> Result<(), String> {
+    let text: String = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let save: SaveFile = ron::from_str(&text).map_err(|e| format!("{}: {}", path, e))?;
+    if save.version != SAVE_VERSION {
+        return Err(format!("{}: save version {} can't be loaded, expected {}", path, save.version, SAVE_VERSION));
+    }
+    if save.data != sim.data_ids() {
+        return Err(format!("{}: save was made with different game data", path));
+    }
+    sim.restore(save);
+    return Ok(());
+}
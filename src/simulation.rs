@@ -0,0 +1,768 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::arena::{Arena, Handle};
+use crate::bloon::{BloonId, BloonTypes, Modifiers};
+use crate::command::Command;
+use crate::clock::DT;
+use crate::data::GameData;
+use crate::difficulty::{Difficulty, Rules};
+use crate::enemy::Enemy;
+use crate::geometry::{pythag, rotate_about};
+use crate::placement::{check_placement, PlacementError};
+use crate::projectile::Projectile;
+use crate::rng::GameRng;
+use crate::round::{Rounds, Spawn};
+use crate::save::{DataIds, SaveFile, SAVE_VERSION};
+use crate::spatial::Grid;
+use crate::status::StatusEffect;
+use crate::targeting::{within_range, Targeting};
+use crate::tower::Tower;
+use crate::tower_type::{Attack, DamageType, ProjectileStats, TowerId, TowerTypes};
+use crate::track::Map;
+
+// The cash paid at the end of every round, on top of one more for each round survived
+const ROUND_BONUS: u32 = 100;
+
+// The percentage of the money spent on a tower that is paid back when it is sold
+const SELL_REFUND: u32 = 70;
+
+// The size of the cells enemies are sorted into for finding projectile hits, in pixels. About
+// the size of the biggest bloons, so a hit test only looks in a few cells.
+const GRID_CELL: f32 = 64.0;
+
+// How far from its centre an enemy is drawn, for finding the enemy under the mouse
+const ENEMY_SIZE: f32 = 10.0;
+
+// A projectile to fire this step, from the tower's position towards a target
+struct Shot {
+    source: (f32,f32),
+    target: (f32,f32),
+    // The enemy being shot at and the tower shooting
+    target_enemy: Handle,
+    tower: Handle,
+    stats: ProjectileStats,
+}
+
+// Damage to deal to an enemy this step, and the effect to apply after it
+struct Hit {
+    enemy: Handle,
+    damage: u32,
+    damage_type: DamageType,
+    effect: Option<StatusEffect>,
+    // The tower credited with whatever is popped
+    tower: Handle,
+}
+
+// The pierce a projectile has left after this step's hits, and the enemies it hit
+struct Pierced {
+    projectile: Handle,
+    pierce: u32,
+    hit: Vec<Handle>,
+}
+
+// Enemies hit, then projectiles with updated pierce values
+type Hits = (Vec<Hit>,Vec<Pierced>);
+
+// Returns the hit for a projectile striking the given enemy
+fn projectile_hit(enemy: Handle, projectile: &Projectile) -> Hit {
+    return Hit {
+        enemy: enemy,
+        damage: projectile.damage,
+        damage_type: projectile.damage_type,
+        effect: projectile.effect,
+        tower: projectile.tower,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum GameState {
+    Playing,
+    // The player has run out of lives, and the simulation no longer advances
+    GameOver,
+    // The player has survived every round, and the simulation no longer advances
+    Won,
+}
+
+// The reason a tower can't be upgraded
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UpgradeError {
+    NoTower,
+    // Every tier on the path has been bought
+    MaxTier,
+    CannotAfford,
+}
+
+impl fmt::Display for UpgradeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UpgradeError::NoTower => return write!(f, "There is no tower there"),
+            UpgradeError::MaxTier => return write!(f, "That path is fully upgraded"),
+            UpgradeError::CannotAfford => return write!(f, "Not enough money for that upgrade"),
+        }
+    }
+}
+
+// Things that happen in the simulation that the player should be told about
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SimEvent {
+    // The round with the given number has started
+    RoundStarted(u32),
+    // The round with the given number has been cleared, paying the given bonus
+    RoundEnded(u32, u32),
+}
+
+// Owns all of the game state and advances it. Nothing in here touches the window, so the
+// simulation can be stepped without macroquad running. It only ever moves forward in fixed
+// steps of DT, and draws all randomness from its seeded rng, so the same seed and inputs
+// always give the same state on the same tick.
+pub struct Simulation {
+    pub state: GameState,
+    // Lives left. Each layer of a bloon that reaches the end of the track costs one life.
+    pub health: u32,
+    pub money: u32,
+    pub map: Map,
+    pub def_target: Targeting,
+    pub enemies: Arena<Enemy>,
+    pub projectiles: Arena<Projectile>,
+    pub towers: Arena<Tower>,
+    pub difficulty: Difficulty,
+    pub bloons: BloonTypes,
+    pub tower_types: TowerTypes,
+    pub rounds: Rounds,
+    pub rng: GameRng,
+    // The number of steps run so far
+    pub tick: u64,
+    pub spawn_count: u32,
+    // The number of rounds finished
+    pub round: u32,
+    // Whether a round is being played, the steps since it started and the bloons still to be
+    // sent in it, latest first
+    pub in_round: bool,
+    round_tick: u64,
+    schedule: Vec<Spawn>,
+    events: Vec<SimEvent>,
+    // The number of layers popped and the number of layers leaked so far
+    pub popped: u32,
+    pub leaked: u32,
+}
+
+impl Simulation {
+    pub fn new(difficulty: Difficulty, data: GameData, def_target: Targeting, seed: u64) -> Simulation {
+        let rules: Rules = difficulty.rules();
+        let enemies: Arena<Enemy> = Arena::new();
+        let projectiles: Arena<Projectile> = Arena::new();
+        let towers: Arena<Tower> = Arena::new();
+        return Simulation {
+            state: GameState::Playing,
+            health: rules.lives,
+            money: rules.money,
+            map: data.map,
+            def_target: def_target,
+            enemies: enemies,
+            projectiles: projectiles,
+            towers: towers,
+            difficulty: difficulty,
+            bloons: data.bloons,
+            tower_types: data.towers,
+            rounds: data.rounds,
+            rng: GameRng::new(seed),
+            tick: 0,
+            spawn_count: 0,
+            round: 0,
+            in_round: false,
+            round_tick: 0,
+            schedule: vec![],
+            events: vec![],
+            popped: 0,
+            leaked: 0,
+        }
+    }
+    fn new_enemy(&mut self, kind: BloonId, modifiers: Modifiers) {
+        let mut n_enemy = Enemy::new(kind, modifiers, kind, &self.bloons);
+        n_enemy.place(0.0, &self.map.track);
+        self.enemies.insert(n_enemy);
+    }
+    fn new_tower(&mut self, kind: TowerId, x: f32, y: f32, target: Targeting, cost: u32) {
+        let n_tower = Tower::new(kind,x,y,target,cost,&self.tower_types);
+        self.towers.insert(n_tower);
+    }
+    fn new_projectile(&mut self, source: (f32,f32), target: (f32,f32), target_enemy: Handle, tower: Handle, stats: &ProjectileStats) {
+        let n_projectile = Projectile::new(source,target,Some(target_enemy),tower,stats);
+        self.projectiles.insert(n_projectile);
+    }
+
+    // Changes the difficulty, resetting lives and money to its starting amounts. Only allowed
+    // before anything has happened in the game. Returns true if it was changed.
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) -> bool {
+        if self.tick > 0 || self.round > 0 || self.in_round || !self.towers.is_empty() {
+            return false;
+        }
+        let rules: Rules = difficulty.rules();
+        self.difficulty = difficulty;
+        self.health = rules.lives;
+        self.money = rules.money;
+        return true;
+    }
+
+    // Returns the number of rounds that must be survived to win on this difficulty
+    pub fn total_rounds(&self) -> u32 {
+        return self.difficulty.rules().rounds.min(self.rounds.len());
+    }
+
+    // Returns what the given cost from the game data costs on this difficulty
+    pub fn price(&self, cost: u32) -> u32 {
+        return (cost as f32 * self.difficulty.rules().cost_factor).round() as u32;
+    }
+
+    // Returns the ids of the game data, which a save must match to be loaded
+    pub fn data_ids(&self) -> DataIds {
+        return DataIds {
+            map: self.map.name.clone(),
+            track: self.map.track.points().to_vec(),
+            bloons: self.bloons.ids(),
+            towers: self.tower_types.ids(),
+            rounds: self.rounds.len(),
+        }
+    }
+
+    // Returns a save of the game as it is now
+    pub fn to_save(&self) -> SaveFile {
+        return SaveFile {
+            version: SAVE_VERSION,
+            data: self.data_ids(),
+            state: self.state,
+            health: self.health,
+            money: self.money,
+            def_target: self.def_target,
+            difficulty: self.difficulty,
+            enemies: self.enemies.clone(),
+            projectiles: self.projectiles.clone(),
+            towers: self.towers.clone(),
+            seed: self.rng.seed(),
+            word_pos: self.rng.word_pos(),
+            tick: self.tick,
+            spawn_count: self.spawn_count,
+            round: self.round,
+            in_round: self.in_round,
+            round_tick: self.round_tick,
+            schedule: self.schedule.clone(),
+            popped: self.popped,
+            leaked: self.leaked,
+        }
+    }
+
+    // Carries on from a save. The save must have been made with the same game data.
+    pub fn restore(&mut self, save: SaveFile) {
+        self.state = save.state;
+        self.health = save.health;
+        self.money = save.money;
+        self.def_target = save.def_target;
+        self.difficulty = save.difficulty;
+        self.enemies = save.enemies;
+        self.projectiles = save.projectiles;
+        self.towers = save.towers;
+        self.rng = GameRng::restore(save.seed, save.word_pos);
+        self.tick = save.tick;
+        self.spawn_count = save.spawn_count;
+        self.round = save.round;
+        self.in_round = save.in_round;
+        self.round_tick = save.round_tick;
+        self.schedule = save.schedule;
+        self.events = vec![];
+        self.popped = save.popped;
+        self.leaked = save.leaked;
+    }
+
+    // Checks that a tower of the given type could be bought at the given position: there is room
+    // for it and the player can afford it
+    pub fn can_place(&self, kind: TowerId, x: f32, y: f32) -> Result<(), PlacementError> {
+        let tower_type = self.tower_types.get(kind);
+        check_placement((x,y), tower_type.radius, tower_type.water, &self.map, &self.towers)?;
+        if self.price(tower_type.cost) > self.money {
+            return Err(PlacementError::CannotAfford);
+        }
+        return Ok(());
+    }
+
+    // Buys a tower of the given type with the default targetting at the given position, if there
+    // is room for it and the player can afford it
+    pub fn place_tower(&mut self, kind: TowerId, x: f32, y: f32) -> Result<(), PlacementError> {
+        self.can_place(kind, x, y)?;
+        let cost: u32 = self.price(self.tower_types.get(kind).cost);
+        self.money -= cost;
+        self.new_tower(kind,x,y,self.def_target,cost);
+        return Ok(());
+    }
+
+    // Returns the tower covering the given position, if there is one
+    pub fn tower_at(&self, x: f32, y: f32) -> Option<Handle> {
+        return self.towers.iter().find(|(_, tower)| pythag((tower.x,tower.y), (x,y)) < tower.radius * tower.radius).map(|(handle, _)| handle);
+    }
+
+    // Returns the enemy covering the given position, if there is one. Anywhere on the drawing of
+    // an enemy counts, even if its hitbox is smaller.
+    pub fn enemy_at(&self, x: f32, y: f32) -> Option<Handle> {
+        return self.enemies.iter().find(|(_, enemy)| {
+            let radius: f32 = enemy.radius.max(ENEMY_SIZE);
+            return pythag((enemy.x,enemy.y), (x,y)) < radius * radius;
+        }).map(|(handle, _)| handle);
+    }
+
+    // Switches the given tower to its next targetting priority
+    pub fn cycle_targeting(&mut self, tower: Handle) {
+        if let Some(tower) = self.towers.get_mut(tower) {
+            tower.cycle_targeting();
+        }
+    }
+
+    // Buys the next upgrade on the given path for the given tower
+    pub fn upgrade_tower(&mut self, tower: Handle, path: usize) -> Result<(), UpgradeError> {
+        let Some(n_tower) = self.towers.get(tower) else {
+            return Err(UpgradeError::NoTower);
+        };
+        let Some(upgrade) = self.tower_types.get(n_tower.kind).next_upgrade(&n_tower.tiers, path) else {
+            return Err(UpgradeError::MaxTier);
+        };
+        let cost: u32 = self.price(upgrade.cost);
+        if cost > self.money {
+            return Err(UpgradeError::CannotAfford);
+        }
+        self.money -= cost;
+        if let Some(n_tower) = self.towers.get_mut(tower) {
+            n_tower.upgrade(path, &self.tower_types);
+            n_tower.spent += cost;
+        }
+        return Ok(());
+    }
+
+    // Returns the money paid back for selling the given tower
+    pub fn sell_value(&self, tower: &Tower) -> u32 {
+        return tower.spent * SELL_REFUND / 100;
+    }
+
+    // Removes the given tower and pays back part of what was spent on it. Returns the money paid
+    // back, or None if there is no such tower.
+    pub fn sell_tower(&mut self, tower: Handle) -> Option<u32> {
+        let n_tower: Tower = self.towers.remove(tower)?;
+        let refund: u32 = self.sell_value(&n_tower);
+        self.money += refund;
+        return Some(refund);
+    }
+
+    // Carries out a player action, returning why it couldn't be done if it fails. Every change
+    // the player makes to a game goes through here, so replaying the same commands on the same
+    // steps plays out the same game. Nothing can be done once the game is over.
+    pub fn execute(&mut self, command: Command) -> Result<(), String> {
+        if self.state != GameState::Playing {
+            return Err(String::from("The game is over"));
+        }
+        match command {
+            Command::PlaceTower { kind, x, y } => return self.place_tower(kind, x, y).map_err(|e| e.to_string()),
+            Command::UpgradeTower { tower, path } => return self.upgrade_tower(tower, path).map_err(|e| e.to_string()),
+            Command::SellTower { tower } => {
+                return self.sell_tower(tower).map(|_| ()).ok_or(UpgradeError::NoTower.to_string());
+            }
+            Command::CycleTargeting { tower } => {
+                self.cycle_targeting(tower);
+                return Ok(());
+            }
+            Command::StartRound => {
+                if !self.start_round() {
+                    return Err(String::from("A round can't be started now"));
+                }
+                return Ok(());
+            }
+        }
+    }
+
+    fn remove_projectile(&mut self, projectile: Handle) {
+        self.projectiles.remove(projectile);
+    }
+
+    // Damages the given enemy. Each layer popped pays its reward, and the enemy is replaced by the
+    // children that survive the leftover damage, which pass through fortified layers more slowly.
+    // Returns true if nothing is left. Enemies that have already been removed, or can't be hurt by
+    // the type of damage, are left alone.
+    fn pop_enemy(&mut self, handle: Handle, damage: u32, damage_type: DamageType) -> bool {
+        let Some(enemy) = self.enemies.get_mut(handle) else {
+            return false;
+        };
+        if !enemy.can_be_hurt_by(damage_type) {
+            return false;
+        }
+        if enemy.health > damage {
+            enemy.health -= damage;
+            return false;
+        }
+        let modifiers: Modifiers = enemy.modifiers.children();
+        let overkill: u32 = (damage - enemy.health) / modifiers.hits_per_layer();
+        let distance: f32 = enemy.distance;
+        let layers: u32 = enemy.layers;
+        let origin: BloonId = enemy.origin;
+        let mut children: Vec<BloonId> = vec![];
+        self.money += self.bloons.pop(enemy.kind, overkill, &mut children);
+        self.popped += layers - children.iter().map(|&child| self.bloons.get(child).layers).sum::<u32>();
+
+        let Some((&first, rest)) = children.split_first() else {
+            return true;
+        };
+        if let Some(enemy) = self.enemies.get_mut(handle) {
+            enemy.modifiers = modifiers;
+            enemy.set_kind(first, &self.bloons);
+            enemy.statuses.thaw();
+        }
+        // The other children start where the popped bloon was
+        for &child in rest {
+            let mut n_enemy = Enemy::new(child, modifiers, origin, &self.bloons);
+            n_enemy.place(distance, &self.map.track);
+            self.enemies.insert(n_enemy);
+        }
+        return false;
+    }
+
+    fn remove_enemy(&mut self, enemy: Handle) {
+        self.enemies.remove(enemy);
+    }
+
+    // Returns the enemies hit with the damage taken and the tower to credit, then projectiles and updated pierce values.
+    // A projectile hits an enemy when they overlap, and each hit uses up one pierce. An enemy is
+    // only hit once by each projectile, so pierce is the number of different enemies it can hit.
+    // Projectiles with splash explode on their first hit, damaging every enemy in the blast.
+    fn enemies_hit(&self) -> Hits {
+        // Stores the enemies hit, then the bullet and new pierce value
+        let mut out: Hits = (vec![],vec![]);
+        let mut grid: Grid<Handle> = Grid::new(GRID_CELL);
+        let mut max_radius: f32 = 0.0;
+        for (enemy_handle, enemy) in self.enemies.iter() {
+            grid.insert(enemy_handle, (enemy.x,enemy.y));
+            max_radius = max_radius.max(enemy.radius);
+        }
+        for (projectile_handle, projectile) in self.projectiles.iter() {
+            if !projectile.can_hit() || projectile.pierce == 0 {
+                continue;
+            }
+            let pos: (f32,f32) = (projectile.x,projectile.y);
+            let mut pierce: u32 = projectile.pierce;
+            let mut hit: Vec<Handle> = vec![];
+            for enemy_handle in grid.near(pos, projectile.radius + max_radius) {
+                if projectile.hit.contains(&enemy_handle) {
+                    continue;
+                }
+                let Some(enemy) = self.enemies.get(enemy_handle) else {
+                    continue;
+                };
+                let reach: f32 = enemy.radius + projectile.radius;
+                if pythag((enemy.x,enemy.y), pos) >= reach * reach {
+                    continue;
+                }
+                if projectile.splash > 0.0 {
+                    pierce = 0;
+                    break;
+                }
+                out.0.push(projectile_hit(enemy_handle, projectile));
+                hit.push(enemy_handle);
+                pierce -= 1;
+                if pierce == 0 {
+                    break;
+                }
+            }
+            // Arcing projectiles with splash explode where they land, whether or not they hit anything
+            let exploded: bool = projectile.splash > 0.0 && (pierce == 0 || projectile.landed());
+            if exploded {
+                let range: f32 = projectile.splash;
+                for enemy_handle in grid.near(pos, range) {
+                    let Some(enemy) = self.enemies.get(enemy_handle) else {
+                        continue;
+                    };
+                    if pythag((enemy.x,enemy.y), pos) < range * range {
+                        out.0.push(projectile_hit(enemy_handle, projectile));
+                    }
+                }
+                pierce = 0;
+            }
+            // Push the projectile and pierce remaining
+            if exploded || pierce != projectile.pierce {
+                out.1.push(Pierced {
+                    projectile: projectile_handle,
+                    pierce: pierce,
+                    hit: hit,
+                });
+            }
+        }
+        return out;
+    }
+
+    // Starts the next round, if the game is still going and no round is being played. Returns
+    // true if a round was started.
+    pub fn start_round(&mut self) -> bool {
+        if self.state != GameState::Playing || self.in_round || self.round >= self.total_rounds() {
+            return false;
+        }
+        self.in_round = true;
+        self.round_tick = 0;
+        self.schedule = self.rounds.schedule(self.round);
+        self.events.push(SimEvent::RoundStarted(self.round + 1));
+        return true;
+    }
+
+    // Returns the events that have happened since this was last called
+    pub fn take_events(&mut self) -> Vec<SimEvent> {
+        return std::mem::take(&mut self.events);
+    }
+
+    // Sends the bloons due on this step of the round
+    fn update_spawns(&mut self) {
+        if !self.in_round {
+            return;
+        }
+        while let Some(&(step, kind, modifiers)) = self.schedule.last() {
+            if step > self.round_tick {
+                break;
+            }
+            self.schedule.pop();
+            self.spawn_count += 1;
+            self.new_enemy(kind, modifiers);
+        }
+        self.round_tick += 1;
+    }
+
+    // Ends the round once every bloon in it has been sent and none are left on the track, paying
+    // the end of round bonus. Surviving the last round wins the game.
+    fn update_round_end(&mut self) {
+        if !self.in_round || !self.schedule.is_empty() || !self.enemies.is_empty() {
+            return;
+        }
+        self.in_round = false;
+        self.round += 1;
+        let bonus: u32 = ROUND_BONUS + self.round;
+        self.money += bonus;
+        self.events.push(SimEvent::RoundEnded(self.round, bonus));
+        if self.round >= self.total_rounds() {
+            self.state = GameState::Won;
+        }
+    }
+
+    // Advances the game by one fixed step, unless the game is over
+    pub fn step(&mut self) {
+        if self.state != GameState::Playing {
+            return;
+        }
+        self.update(DT);
+        self.tick += 1;
+    }
+
+    // Removes enemies that have reached the end of the track, taking a life for each layer they
+    // had left, and ends the game if that was the last of the player's lives
+    fn update_leaks(&mut self) {
+        let length: f32 = self.map.track.length();
+        let mut leaked: u32 = 0;
+        self.enemies.retain(|enemy| {
+            if enemy.distance >= length {
+                leaked += enemy.layers;
+                return false;
+            }
+            return true;
+        });
+        self.leaked += leaked;
+        self.health = self.health.saturating_sub(leaked);
+        if self.health == 0 {
+            self.state = GameState::GameOver;
+        }
+    }
+
+    // Updates spawns, enemies, towers, projectiles and the round
+    fn update(&mut self, dt: f32) {
+
+        self.update_spawns();
+        let pace: f32 = self.difficulty.rules().speed_factor;
+
+        // Update enemies, holding on to the damage done by burns
+        let mut burns: Vec<Hit> = vec![];
+        for (handle, enemy) in self.enemies.iter_mut() {
+            for (damage, tower) in enemy.update(dt,pace,&self.map.track,&self.bloons) {
+                burns.push(Hit {
+                    enemy: handle,
+                    damage: damage,
+                    damage_type: DamageType::Fire,
+                    effect: None,
+                    tower: tower,
+                });
+            }
+        }
+        self.update_leaks();
+        if self.state == GameState::GameOver {
+            return;
+        }
+
+        // Holds the projectiles fired by towers, aimed at enemy positions shifted by the tower spread
+        let mut shots: Vec<Shot> = vec![];
+
+        // Holds the enemies hit instantly
+        let mut instant_hits: Vec<Hit> = vec![];
+        
+        // Update towers
+        for (tower_handle, tower) in self.towers.iter_mut() {
+            if tower.can_shoot(dt) {
+                let tower_pos = tower.get_pos();
+                let targeting: Targeting = tower.get_target();
+                let Some(target) = targeting.select(tower_pos, &self.enemies, tower.stats.reach(), tower.stats.camo) else {
+                    continue;
+                };
+                let Some(target_enemy) = self.enemies.get(target) else {
+                    continue;
+                };
+                match tower.stats.attack {
+                    Attack::Instant => instant_hits.push(Hit {
+                        enemy: target,
+                        damage: tower.stats.projectile.damage,
+                        damage_type: tower.stats.projectile.damage_type,
+                        effect: tower.stats.projectile.effect,
+                        tower: tower_handle,
+                    }),
+                    Attack::Aura => {
+                        let Some(effect) = tower.stats.projectile.effect else {
+                            continue;
+                        };
+                        let affected: Vec<Handle> = within_range(tower_pos, &self.enemies, tower.stats.reach(), tower.stats.camo).into_iter().map(|(handle, _)| handle).collect();
+                        for handle in affected {
+                            if let Some(enemy) = self.enemies.get_mut(handle) {
+                                enemy.apply_effect(effect, tower_handle);
+                            }
+                        }
+                    }
+                    Attack::Single | Attack::Spread { .. } | Attack::Radial { .. } => {
+                        let angle: f32 = self.rng.range(-tower.spread, tower.spread);
+                        let aim: (f32,f32) = rotate_about((target_enemy.x,target_enemy.y), tower_pos, angle);
+                        for offset in tower.stats.attack.angles() {
+                            shots.push(Shot {
+                                source: tower_pos,
+                                target: rotate_about(aim, tower_pos, offset),
+                                target_enemy: target,
+                                tower: tower_handle,
+                                stats: tower.stats.projectile,
+                            });
+                        }
+                    }
+                }
+                tower.reset_cooldown();
+            }
+        }
+
+        for shot in shots {
+            self.new_projectile(shot.source,shot.target,shot.target_enemy,shot.tower,&shot.stats);
+        }
+
+        let mut projectiles_to_remove: Vec<Handle> = vec![];
+
+        for (handle, projectile) in self.projectiles.iter_mut() {
+            let target: Option<(f32,f32)> = projectile.target
+                .and_then(|enemy| self.enemies.get(enemy))
+                .map(|enemy| (enemy.x,enemy.y));
+            if projectile.update(dt, target) {
+                projectiles_to_remove.push(handle);
+            }
+        }
+
+        for handle in projectiles_to_remove {
+            self.remove_projectile(handle);
+        }
+
+        let mut hits: Hits = self.enemies_hit();
+        hits.0.extend(instant_hits);
+        hits.0.extend(burns);
+
+        // Pop enemy layers, removing enemies with no layers left and crediting the towers that
+        // popped them, then apply the hit's effect to whatever is left. Hits on enemies that have
+        // already been removed are skipped.
+        for hit in hits.0 {
+            let popped: u32 = self.popped;
+            if self.pop_enemy(hit.enemy, hit.damage, hit.damage_type) {
+                self.remove_enemy(hit.enemy);
+            }
+            if let Some(tower) = self.towers.get_mut(hit.tower) {
+                tower.popped += self.popped - popped;
+            }
+            if let (Some(effect), Some(enemy)) = (hit.effect, self.enemies.get_mut(hit.enemy)) {
+                enemy.apply_effect(effect, hit.tower);
+            }
+        }
+
+        // Update projectile pierce and remove projectiles with 0 pierce
+        for pierced in hits.1 {
+            if let Some(projectile) = self.projectiles.get_mut(pierced.projectile) {
+                projectile.pierce = pierced.pierce;
+                projectile.hit.extend(pierced.hit);
+                if pierced.pierce == 0 {
+                    self.remove_projectile(pierced.projectile);
+                }
+            }
+        }
+
+        self.update_round_end();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A game with the built in data and nothing in it
+    fn empty_sim() -> Simulation {
+        let data: GameData = GameData::load(None, None, None, None).expect("the built in data loads");
+        return Simulation::new(Difficulty::Medium, data, Targeting::First, 1);
+    }
+
+    #[test]
+    fn commands_refused_once_game_is_over() {
+        let mut sim: Simulation = empty_sim();
+        sim.state = GameState::GameOver;
+        let money: u32 = sim.money;
+        assert!(sim.execute(Command::PlaceTower { kind: TowerId(0), x: 400.0, y: 300.0 }).is_err());
+        assert!(sim.towers.is_empty());
+        assert_eq!(sim.money, money);
+    }
+
+    #[test]
+    fn projectile_hits_each_enemy_once() {
+        let mut sim: Simulation = empty_sim();
+        let green: BloonId = sim.bloons.find("green").expect("there is a green bloon");
+        let mut enemy: Enemy = Enemy::new(green, Modifiers::default(), green, &sim.bloons);
+        enemy.place(200.0, &sim.map.track);
+        enemy.speed = 0.0;
+        let pos: (f32,f32) = (enemy.x,enemy.y);
+        sim.enemies.insert(enemy);
+        // A still projectile sitting on the enemy, which would hit it every step if it could
+        let mut stats: ProjectileStats = sim.tower_types.get(TowerId(0)).stats.projectile;
+        stats.speed = 0.0;
+        stats.damage = 1;
+        stats.pierce = 3;
+        stats.lifetime = 10.0;
+        let tower: Handle = Arena::new().insert(());
+        sim.projectiles.insert(Projectile::new(pos, (pos.0 + 1.0, pos.1), None, tower, &stats));
+        for _ in 0..10 {
+            sim.step();
+        }
+        assert_eq!(sim.popped, 1);
+        let projectile: &Projectile = sim.projectiles.values().next().expect("the projectile has pierce left");
+        assert_eq!(projectile.pierce, 2);
+    }
+
+    #[test]
+    fn global_tower_hits_beyond_its_range() {
+        let mut sim: Simulation = empty_sim();
+        let sniper: TowerId = TowerId(3);
+        assert!(sim.tower_types.get(sniper).stats.global);
+        sim.place_tower(sniper, 780.0, 560.0).expect("the sniper can be placed");
+        let red: BloonId = sim.bloons.find("red").expect("there is a red bloon");
+        let mut enemy: Enemy = Enemy::new(red, Modifiers::default(), red, &sim.bloons);
+        enemy.place(10.0, &sim.map.track);
+        enemy.speed = 0.0;
+        sim.enemies.insert(enemy);
+        for _ in 0..120 {
+            sim.step();
+        }
+        assert_eq!(sim.popped, 1);
+    }
+}
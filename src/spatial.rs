@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+
+// Sorts points into square cells of a fixed size, so the points near a position can be found by
+// looking in the few cells around it rather than checking every point. Each point is stored as
+// a key, such as the handle of the thing at that point.
+pub struct Grid<K> {
+    cell: f32,
+    cells: HashMap<(i32,i32), Vec<K>>,
+}
+
+impl<K: Copy + Ord> Grid<K> {
+    pub fn new(cell: f32) -> Grid<K> {
+        return Grid {
+            cell: cell.max(1.0),
+            cells: HashMap::new(),
+        }
+    }
+
+    fn cell_of(&self, pos: (f32,f32)) -> (i32,i32) {
+        return ((pos.0 / self.cell).floor() as i32, (pos.1 / self.cell).floor() as i32);
+    }
+
+    // Adds the point with the given key at the given position
+    pub fn insert(&mut self, key: K, pos: (f32,f32)) {
+        let cell: (i32,i32) = self.cell_of(pos);
+        self.cells.entry(cell).or_default().push(key);
+    }
+
+    // Returns the keys of the points in every cell within the given distance of the position,
+    // in ascending order. Some of them may be further away than the distance, so callers still
+    // need to check each one.
+    pub fn near(&self, pos: (f32,f32), distance: f32) -> Vec<K> {
+        let low: (i32,i32) = self.cell_of((pos.0 - distance, pos.1 - distance));
+        let high: (i32,i32) = self.cell_of((pos.0 + distance, pos.1 + distance));
+        let mut near: Vec<K> = vec![];
+        for x in low.0..=high.0 {
+            for y in low.1..=high.1 {
+                if let Some(cell) = self.cells.get(&(x,y)) {
+                    near.extend(cell);
+                }
+            }
+        }
+        // The cells are stored in no particular order, so sort to keep the simulation deterministic
+        near.sort_unstable();
+        return near;
+    }
+}
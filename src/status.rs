@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+
+use crate::arena::Handle;
+
+// The most burns that can be on one enemy at once
+const MAX_BURNS: usize = 3;
+
+// How long a thawed enemy can't be frozen again, in seconds
+const THAW_TIME: f32 = 1.0;
+
+// Something that happens to an enemy for a while
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Effect {
+    // Moves at the given fraction of its speed
+    Slow { factor: f32 },
+    // Stops moving and can't be hurt by sharp damage
+    Freeze,
+    // Takes the given damage every interval, in seconds
+    Burn { damage: u32, interval: f32 },
+    // Stops moving
+    Stun,
+}
+
+// An effect and how many seconds it lasts, as given in the tower data
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub effect: Effect,
+    pub duration: f32,
+}
+
+// An effect on an enemy, with the seconds it has left and the tower that caused it
+#[derive(Clone, Serialize, Deserialize)]
+struct Status {
+    effect: Effect,
+    time_left: f32,
+    // The seconds since a burn last did damage
+    timer: f32,
+    tower: Handle,
+}
+
+// The effects on an enemy. Each kind of effect stacks differently:
+// - Only the strongest slow applies. The same slow again refreshes how long it lasts, and weaker
+//   slows are ignored while it lasts.
+// - Freezes and stuns don't stack. Applying them again refreshes how long they last, but an enemy
+//   can't be frozen again until a moment after it thaws.
+// - Burns stack up to MAX_BURNS, after which a new burn replaces the one closest to running out.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Statuses {
+    statuses: Vec<Status>,
+    // The seconds left before the enemy can be frozen again
+    thaw: f32,
+}
+
+impl Statuses {
+    pub fn new() -> Statuses {
+        return Statuses {
+            statuses: vec![],
+            thaw: 0.0,
+        }
+    }
+
+    // Adds an effect caused by the given tower, following the stacking rules
+    pub fn apply(&mut self, effect: StatusEffect, tower: Handle) {
+        let n_status: Status = Status {
+            effect: effect.effect,
+            time_left: effect.duration,
+            timer: 0.0,
+            tower: tower,
+        };
+        match effect.effect {
+            Effect::Slow { factor } => {
+                let slowest: Option<&mut Status> = self.statuses.iter_mut().find(|status| matches!(status.effect, Effect::Slow { .. }));
+                match slowest {
+                    Some(status) => {
+                        let Effect::Slow { factor: current } = status.effect else {
+                            return;
+                        };
+                        if factor < current {
+                            *status = n_status;
+                        } else if factor == current {
+                            status.time_left = status.time_left.max(effect.duration);
+                        }
+                    }
+                    None => self.statuses.push(n_status),
+                }
+            }
+            Effect::Freeze | Effect::Stun => {
+                if effect.effect == Effect::Freeze && self.thaw > 0.0 {
+                    return;
+                }
+                match self.statuses.iter_mut().find(|status| status.effect == effect.effect) {
+                    Some(status) => status.time_left = status.time_left.max(effect.duration),
+                    None => self.statuses.push(n_status),
+                }
+            }
+            Effect::Burn { .. } => {
+                let burns: usize = self.statuses.iter().filter(|status| matches!(status.effect, Effect::Burn { .. })).count();
+                if burns < MAX_BURNS {
+                    self.statuses.push(n_status);
+                    return;
+                }
+                let shortest: Option<&mut Status> = self.statuses.iter_mut()
+                    .filter(|status| matches!(status.effect, Effect::Burn { .. }))
+                    .min_by(|a, b| a.time_left.total_cmp(&b.time_left));
+                if let Some(status) = shortest {
+                    *status = n_status;
+                }
+            }
+        }
+    }
+
+    // Counts down every effect, removing the ones that have run out. Returns the damage done by
+    // burns this step, with the tower that caused each burn.
+    pub fn update(&mut self, dt: f32) -> Vec<(u32, Handle)> {
+        let mut burns: Vec<(u32, Handle)> = vec![];
+        let was_frozen: bool = self.frozen();
+        self.thaw = (self.thaw - dt).max(0.0);
+        for status in &mut self.statuses {
+            status.time_left -= dt;
+            if let Effect::Burn { damage, interval } = status.effect {
+                status.timer += dt;
+                if status.timer >= interval {
+                    status.timer -= interval;
+                    burns.push((damage, status.tower));
+                }
+            }
+        }
+        self.statuses.retain(|status| status.time_left > 0.0);
+        if was_frozen && !self.frozen() {
+            self.thaw = THAW_TIME;
+        }
+        return burns;
+    }
+
+    // Ends any freeze straight away, as when the ice is broken by popping the layer underneath
+    pub fn thaw(&mut self) {
+        if self.frozen() {
+            self.statuses.retain(|status| status.effect != Effect::Freeze);
+            self.thaw = THAW_TIME;
+        }
+    }
+
+    // Returns the fraction of its speed the enemy moves at
+    pub fn speed_factor(&self) -> f32 {
+        let mut factor: f32 = 1.0;
+        for status in &self.statuses {
+            match status.effect {
+                Effect::Slow { factor: slow } => factor = factor.min(slow),
+                Effect::Freeze | Effect::Stun => return 0.0,
+                Effect::Burn { .. } => {}
+            }
+        }
+        return factor;
+    }
+
+    pub fn frozen(&self) -> bool {
+        return self.statuses.iter().any(|status| status.effect == Effect::Freeze);
+    }
+
+    pub fn slowed(&self) -> bool {
+        return self.statuses.iter().any(|status| matches!(status.effect, Effect::Slow { .. }));
+    }
+
+    pub fn burning(&self) -> bool {
+        return self.statuses.iter().any(|status| matches!(status.effect, Effect::Burn { .. }));
+    }
+
+    pub fn stunned(&self) -> bool {
+        return self.statuses.iter().any(|status| status.effect == Effect::Stun);
+    }
+}
+
+impl Default for Statuses {
+    fn default() -> Statuses {
+        return Statuses::new();
+    }
+}
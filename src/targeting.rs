@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+
+use crate::arena::{Arena, Handle};
+use crate::enemy::Enemy;
+use crate::geometry::pythag;
+
+// Which enemy in range a tower shoots at
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Targeting {
+    // The enemy furthest along the track
+    First,
+    // The enemy least far along the track
+    Last,
+    // The enemy with the most layers left
+    Strong,
+    // The enemy with the fewest layers left
+    Weak,
+    // The enemy nearest the tower
+    Close,
+}
+
+impl Targeting {
+    // Returns the priority that follows this one, wrapping back round to First
+    pub fn next(self) -> Targeting {
+        match self {
+            Targeting::First => return Targeting::Last,
+            Targeting::Last => return Targeting::Strong,
+            Targeting::Strong => return Targeting::Weak,
+            Targeting::Weak => return Targeting::Close,
+            Targeting::Close => return Targeting::First,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Targeting::First => return "First",
+            Targeting::Last => return "Last",
+            Targeting::Strong => return "Strong",
+            Targeting::Weak => return "Weak",
+            Targeting::Close => return "Close",
+        }
+    }
+
+    // Returns the handle of the enemy within range of the given position to shoot at. Camo
+    // enemies are only picked if camo is true.
+    pub fn select(self, pos: (f32,f32), enemies: &Arena<Enemy>, range: f32, camo: bool) -> Option<Handle> {
+        match self {
+            Targeting::First => return target_first(pos, enemies, range, camo),
+            Targeting::Last => return target_last(pos, enemies, range, camo),
+            Targeting::Strong => return target_strong(pos, enemies, range, camo),
+            Targeting::Weak => return target_weak(pos, enemies, range, camo),
+            Targeting::Close => return target_close(pos, enemies, range, camo),
+        }
+    }
+}
+
+// Returns the enemies within range of the given position, with their handles. Camo enemies are
+// only included if camo is true.
+pub fn within_range(pos: (f32,f32), enemies: &Arena<Enemy>, range: f32, camo: bool) -> Vec<(Handle,&Enemy)> {
+    let mut within: Vec<(Handle,&Enemy)> = vec![];
+    for (handle, enemy) in enemies.iter() {
+        if enemy.modifiers.camo && !camo {
+            continue;
+        }
+        let distance: f32 = pythag(pos, (enemy.x,enemy.y));
+        if distance < range * range {
+            within.push((handle, enemy));
+        }
+    }
+    return within;
+}
+
+fn target_first(pos: (f32,f32), enemies: &Arena<Enemy>, range: f32, camo: bool) -> Option<Handle> {
+    let mut furthest_dist: f32 = 0.0;
+    let within: Vec<(Handle,&Enemy)> = within_range(pos, enemies, range, camo);
+    let mut target: Option<Handle> = None;
+    for (handle, enemy) in within {
+        let distance = enemy.distance;
+        if distance > furthest_dist {
+            furthest_dist = distance;
+            target = Some(handle);
+        }
+        
+    }
+    return target;
+}
+
+fn target_last(pos: (f32,f32), enemies: &Arena<Enemy>, range: f32, camo: bool) -> Option<Handle> {
+    let within: Vec<(Handle,&Enemy)> = within_range(pos, enemies, range, camo);
+    return within.into_iter().min_by(|(_, a), (_, b)| a.distance.total_cmp(&b.distance)).map(|(handle, _)| handle);
+}
+
+// Picks the enemy with the most layers left, then the most hits left on its current layer, then
+// the one furthest along the track
+fn target_strong(pos: (f32,f32), enemies: &Arena<Enemy>, range: f32, camo: bool) -> Option<Handle> {
+    let within: Vec<(Handle,&Enemy)> = within_range(pos, enemies, range, camo);
+    return within.into_iter().max_by(|(_, a), (_, b)| {
+        (a.layers, a.health).cmp(&(b.layers, b.health)).then(a.distance.total_cmp(&b.distance))
+    }).map(|(handle, _)| handle);
+}
+
+// Picks the enemy with the fewest layers left, then the fewest hits left on its current layer,
+// then the one furthest along the track
+fn target_weak(pos: (f32,f32), enemies: &Arena<Enemy>, range: f32, camo: bool) -> Option<Handle> {
+    let within: Vec<(Handle,&Enemy)> = within_range(pos, enemies, range, camo);
+    return within.into_iter().min_by(|(_, a), (_, b)| {
+        (a.layers, a.health).cmp(&(b.layers, b.health)).then(b.distance.total_cmp(&a.distance))
+    }).map(|(handle, _)| handle);
+}
+
+fn target_close(pos: (f32,f32), enemies: &Arena<Enemy>, range: f32, camo: bool) -> Option<Handle> {
+    let within: Vec<(Handle,&Enemy)> = within_range(pos, enemies, range, camo);
+    return within.into_iter().min_by(|(_, a), (_, b)| pythag(pos, (a.x,a.y)).total_cmp(&pythag(pos, (b.x,b.y)))).map(|(handle, _)| handle);
+}
@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+use crate::targeting::Targeting;
+use crate::tower_type::{TowerId, TowerStats, TowerTypes};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Tower {
+    pub kind: TowerId,
+    pub x: f32,
+    pub y: f32,
+    pub targeting: Targeting,
+    pub radius: f32,
+    // The tiers bought on each upgrade path
+    pub tiers: Vec<u32>,
+    // The stats of the tower type with the bought upgrades applied
+    pub stats: TowerStats,
+    // The most a shot can stray from its target, in radians
+    pub spread: f32,
+    cooldown: f32,
+    // The number of layers popped by this tower's shots
+    pub popped: u32,
+    // The money spent on the tower and its upgrades
+    pub spent: u32,
+}
+
+impl Tower {
+    // Creates a tower that was bought for the given cost
+    pub fn new(kind: TowerId, x: f32, y: f32, targeting: Targeting, cost: u32, tower_types: &TowerTypes) -> Tower {
+        let tower_type = tower_types.get(kind);
+        return Tower {
+            kind: kind,
+            x: x,
+            y: y,
+            targeting: targeting,
+            radius: tower_type.radius,
+            tiers: vec![0; tower_type.upgrades.len()],
+            stats: tower_type.stats,
+            spread: 0.05,
+            cooldown: tower_type.stats.cooldown,
+            popped: 0,
+            spent: cost,
+        }
+    }
+
+    // Buys the next tier on the given upgrade path and updates the tower's stats
+    pub fn upgrade(&mut self, path: usize, tower_types: &TowerTypes) {
+        if let Some(tier) = self.tiers.get_mut(path) {
+            *tier += 1;
+        }
+        self.stats = tower_types.get(self.kind).stats_with(&self.tiers);
+    }
+
+    // Returns true when the cooldown period elapses
+    pub fn can_shoot(&mut self, dt: f32) -> bool {
+        self.cooldown -= dt;
+        if self.cooldown < 0.0 {
+            return true;
+        }
+        return false;
+    }
+    pub fn reset_cooldown(&mut self) {
+        self.cooldown = self.stats.cooldown;
+    }
+    // Returns the position of the tower
+    pub fn get_pos(&self) -> (f32,f32) {
+       return (self.x,self.y);
+    }
+
+    // Returns the targetting priority
+    pub fn get_target(&self) -> Targeting {
+        return self.targeting;
+    }
+
+    // Switches to the next targetting priority
+    pub fn cycle_targeting(&mut self) {
+        self.targeting = self.targeting.next();
+    }
+}
@@ -0,0 +1,254 @@
+use serde::{Deserialize, Serialize};
+
+use crate::geometry::PI;
+use crate::status::StatusEffect;
+
+// The tower types shipped with the game, used when there is no towers file next to the game
+pub const DEFAULT_TOWERS: &str = include_str!("../assets/towers.ron");
+
+// The index of a tower type in the loaded list
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct TowerId(pub usize);
+
+// How a tower fires at its target
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Attack {
+    // One projectile straight at the target
+    Single,
+    // A fan of projectiles centred on the target, spread over the given angle in radians
+    Spread { count: u32, angle: f32 },
+    // A ring of projectiles fired evenly in every direction
+    Radial { count: u32 },
+    // Hits the target straight away without firing a projectile
+    Instant,
+    // Applies the projectile's effect to every enemy in range without firing anything
+    Aura,
+}
+
+impl Attack {
+    // Returns the angle of each projectile fired, in radians from the line to the target. Attacks
+    // that don't fire projectiles return no angles.
+    pub fn angles(self) -> Vec<f32> {
+        match self {
+            Attack::Single => return vec![0.0],
+            Attack::Radial { count } => return (0..count).map(|i| 2.0 * PI * i as f32 / count as f32).collect(),
+            Attack::Instant | Attack::Aura => return vec![],
+            Attack::Spread { count, angle } => {
+                if count <= 1 {
+                    return vec![0.0; count as usize];
+                }
+                return (0..count).map(|i| angle * (i as f32 / (count - 1) as f32 - 0.5)).collect();
+            }
+        }
+    }
+}
+
+// What a projectile does damage with. Some bloons can't be hurt by some types.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum DamageType {
+    // Can't hurt lead or frozen bloons
+    #[default]
+    Sharp,
+    Explosive,
+    Fire,
+    // Hurts every bloon
+    Normal,
+}
+
+// The path a projectile follows once it is fired
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum PathKind {
+    // Flies straight at where the target was
+    #[default]
+    Straight,
+    // Follows its target, turning by at most the given radians per second
+    Homing { turn_rate: f32 },
+    // Loops out past the target and comes back to the tower over its lifetime
+    Boomerang,
+    // Lobs over everything to land where the target was, reaching the given height on the way
+    Arc { height: f32 },
+    // Circles the tower at the given distance
+    Orbit { radius: f32 },
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ProjectileStats {
+    // Pixels per second
+    pub speed: f32,
+    // The number of bloons the projectile can hit before it is used up
+    pub pierce: u32,
+    // The number of layers popped by each hit
+    pub damage: u32,
+    pub radius: f32,
+    // Seconds before the projectile disappears
+    pub lifetime: f32,
+    // When above zero, the projectile explodes on its first hit, damaging every bloon within this
+    // many pixels
+    #[serde(default)]
+    pub splash: f32,
+    #[serde(default)]
+    pub path: PathKind,
+    #[serde(default)]
+    pub damage_type: DamageType,
+    // Applied to every bloon the projectile hits
+    #[serde(default)]
+    pub effect: Option<StatusEffect>,
+}
+
+// The numbers that decide how a tower fights. Upgrades change these.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct TowerStats {
+    #[serde(default)]
+    pub range: f32,
+    // Whether the tower can reach anywhere on the map, whatever its range
+    #[serde(default)]
+    pub global: bool,
+    // Seconds between shots
+    pub cooldown: f32,
+    pub attack: Attack,
+    pub projectile: ProjectileStats,
+    // Whether the tower can see camo bloons
+    #[serde(default)]
+    pub camo: bool,
+}
+
+// One tier of an upgrade path. Every change is added on top of the tower's current stats, and
+// the attack, projectile path, damage type and effect, if given, replace the tower's.
+#[derive(Clone, Deserialize)]
+pub struct Upgrade {
+    pub name: String,
+    pub cost: u32,
+    #[serde(default)]
+    pub range: f32,
+    #[serde(default)]
+    pub cooldown: f32,
+    #[serde(default)]
+    pub pierce: u32,
+    #[serde(default)]
+    pub damage: u32,
+    #[serde(default)]
+    pub speed: f32,
+    #[serde(default)]
+    pub splash: f32,
+    #[serde(default)]
+    pub lifetime: f32,
+    // Gives the tower camo detection
+    #[serde(default)]
+    pub camo: bool,
+    #[serde(default)]
+    pub attack: Option<Attack>,
+    #[serde(default)]
+    pub path: Option<PathKind>,
+    #[serde(default)]
+    pub damage_type: Option<DamageType>,
+    #[serde(default)]
+    pub effect: Option<StatusEffect>,
+}
+
+// The shortest time allowed between shots, however many upgrades lower the cooldown
+const MIN_COOLDOWN: f32 = 0.05;
+
+impl TowerStats {
+    // Returns how far away the tower can hit bloons
+    pub fn reach(&self) -> f32 {
+        if self.global {
+            return f32::INFINITY;
+        }
+        return self.range;
+    }
+
+    // Returns these stats with the upgrade applied
+    pub fn upgraded(self, upgrade: &Upgrade) -> TowerStats {
+        let mut stats: TowerStats = self;
+        stats.range += upgrade.range;
+        stats.cooldown = (stats.cooldown + upgrade.cooldown).max(MIN_COOLDOWN);
+        stats.projectile.pierce += upgrade.pierce;
+        stats.projectile.damage += upgrade.damage;
+        stats.projectile.speed += upgrade.speed;
+        stats.projectile.splash += upgrade.splash;
+        stats.projectile.lifetime += upgrade.lifetime;
+        stats.camo |= upgrade.camo;
+        if let Some(attack) = upgrade.attack {
+            stats.attack = attack;
+        }
+        if let Some(path) = upgrade.path {
+            stats.projectile.path = path;
+        }
+        if let Some(damage_type) = upgrade.damage_type {
+            stats.projectile.damage_type = damage_type;
+        }
+        if let Some(effect) = upgrade.effect {
+            stats.projectile.effect = Some(effect);
+        }
+        return stats;
+    }
+}
+
+#[derive(Deserialize)]
+pub struct TowerType {
+    pub id: String,
+    pub name: String,
+    pub cost: u32,
+    pub radius: f32,
+    pub colour: (u8,u8,u8),
+    // Whether the tower is built on water rather than land
+    #[serde(default)]
+    pub water: bool,
+    pub stats: TowerStats,
+    // Each upgrade path is a list of tiers, bought in order
+    pub upgrades: Vec<Vec<Upgrade>>,
+}
+
+impl TowerType {
+    // Returns the stats of this tower with the given number of tiers bought on each path
+    pub fn stats_with(&self, tiers: &[u32]) -> TowerStats {
+        let mut stats: TowerStats = self.stats;
+        for (path, &tier) in self.upgrades.iter().zip(tiers) {
+            for upgrade in path.iter().take(tier as usize) {
+                stats = stats.upgraded(upgrade);
+            }
+        }
+        return stats;
+    }
+
+    // Returns the next upgrade on the given path for a tower with the given tiers bought, if
+    // there is one
+    pub fn next_upgrade(&self, tiers: &[u32], path: usize) -> Option<&Upgrade> {
+        let tier: u32 = *tiers.get(path)?;
+        return self.upgrades.get(path)?.get(tier as usize);
+    }
+}
+
+pub struct TowerTypes {
+    types: Vec<TowerType>,
+}
+
+impl TowerTypes {
+    // Parses a list of tower types, checking that there is at least one and the ids are unique
+    pub fn parse(text: &str) -> Result<TowerTypes, String> {
+        let types: Vec<TowerType> = ron::from_str(text).map_err(|e| e.to_string())?;
+        if types.is_empty() {
+            return Err(String::from("there must be at least one tower type"));
+        }
+        for tower in &types {
+            if types.iter().filter(|other| other.id == tower.id).count() > 1 {
+                return Err(format!("tower '{}' is defined more than once", tower.id));
+            }
+        }
+        return Ok(TowerTypes { types: types });
+    }
+
+    pub fn get(&self, id: TowerId) -> &TowerType {
+        return &self.types[id.0];
+    }
+
+    // Returns the id of every tower type, in order
+    pub fn ids(&self) -> Vec<String> {
+        return self.types.iter().map(|tower| tower.id.clone()).collect();
+    }
+
+    // Returns the number of tower types
+    pub fn len(&self) -> usize {
+        return self.types.len();
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::geometry::{distance_to_segment, pythag_sqrt};
+
+// How wide the track is drawn, and so how much room towers must leave around it
+pub const TRACK_WIDTH: f32 = 20.0;
+
+// The size of the square cells that blocked and water areas are painted in, in pixels
+pub const AREA_CELL: f32 = 20.0;
+
+// The size of a map that doesn't give one, which is the default window size
+pub fn default_size() -> (f32,f32) {
+    return (800.0, 600.0);
+}
+
+// The map shipped with the game, used when there is no map file next to the game
+pub const DEFAULT_MAP: &str = include_str!("../assets/maps/simple.ron");
+
+// A map as written in the map file
+#[derive(Serialize, Deserialize)]
+struct MapFile {
+    name: String,
+    #[serde(default = "default_size")]
+    size: (f32,f32),
+    waypoints: Vec<(f32,f32)>,
+    // The cells, counted in AREA_CELL steps from the top left, that are blocked or water
+    #[serde(default)]
+    blocked: Vec<(i32,i32)>,
+    #[serde(default)]
+    water: Vec<(i32,i32)>,
+}
+
+// What covers a cell of the map other than grass. Nothing can be built on blocked ground, and
+// only water towers can be built on water.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Ground {
+    Blocked,
+    Water,
+}
+
+// Returns the cell of the map containing the given position
+pub fn cell_at(pos: (f32,f32)) -> (i32,i32) {
+    return ((pos.0 / AREA_CELL).floor() as i32, (pos.1 / AREA_CELL).floor() as i32);
+}
+
+// A path made of straight lines between waypoints, measured by the distance travelled along it
+pub struct Track {
+    points: Vec<(f32,f32)>,
+    // The distance along the track at which each waypoint is reached
+    lengths: Vec<f32>,
+}
+
+impl Track {
+    pub fn new(points: Vec<(f32,f32)>) -> Result<Track, String> {
+        if points.len() < 2 {
+            return Err(String::from("a track needs at least two waypoints"));
+        }
+        let mut lengths: Vec<f32> = vec![0.0];
+        for pair in points.windows(2) {
+            let total: f32 = lengths[lengths.len() - 1];
+            lengths.push(total + pythag_sqrt(pair[0], pair[1]));
+        }
+        if lengths[lengths.len() - 1] <= 0.0 {
+            return Err(String::from("a track must have some length"));
+        }
+        return Ok(Track {
+            points: points,
+            lengths: lengths,
+        });
+    }
+
+    // Returns the total length of the track
+    pub fn length(&self) -> f32 {
+        return self.lengths[self.lengths.len() - 1];
+    }
+
+    // Returns the index of the segment containing the given distance, and how far along it the
+    // distance is from 0 to 1. Distances off either end are clamped to the track.
+    fn segment_at(&self, distance: f32) -> (usize, f32) {
+        let distance: f32 = distance.clamp(0.0, self.length());
+        let end: usize = self.lengths.partition_point(|&length| length < distance).clamp(1, self.points.len() - 1);
+        let start: usize = end - 1;
+        let segment: f32 = self.lengths[end] - self.lengths[start];
+        if segment <= 0.0 {
+            return (start, 0.0);
+        }
+        return (start, (distance - self.lengths[start]) / segment);
+    }
+
+    // Returns the position at the given distance along the track
+    pub fn position_at(&self, distance: f32) -> (f32,f32) {
+        let (start, t) = self.segment_at(distance);
+        let (a, b) = (self.points[start], self.points[start + 1]);
+        return (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t);
+    }
+
+    // Returns the direction of travel at the given distance along the track, as a unit vector
+    #[allow(dead_code)]
+    pub fn tangent_at(&self, distance: f32) -> (f32,f32) {
+        let (start, _) = self.segment_at(distance);
+        let (a, b) = (self.points[start], self.points[start + 1]);
+        let segment: f32 = pythag_sqrt(a, b);
+        if segment <= 0.0 {
+            return (1.0, 0.0);
+        }
+        return ((b.0 - a.0) / segment, (b.1 - a.1) / segment);
+    }
+
+    // Returns the distance from the given point to the nearest point on the track
+    pub fn distance_to(&self, point: (f32,f32)) -> f32 {
+        let mut nearest: f32 = f32::INFINITY;
+        for pair in self.points.windows(2) {
+            nearest = nearest.min(distance_to_segment(point, pair[0], pair[1]));
+        }
+        return nearest;
+    }
+
+    // Returns the waypoints of the track
+    pub fn points(&self) -> &[(f32,f32)] {
+        return &self.points;
+    }
+}
+
+pub struct Map {
+    pub name: String,
+    // The width and height of the map in pixels
+    pub size: (f32,f32),
+    pub track: Track,
+    // The cells that are blocked or water. Every other cell is grass.
+    pub areas: HashMap<(i32,i32), Ground>,
+}
+
+impl Map {
+    pub fn parse(text: &str) -> Result<Map, String> {
+        let file: MapFile = ron::from_str(text).map_err(|e| e.to_string())?;
+        let mut areas: HashMap<(i32,i32), Ground> = HashMap::new();
+        for cell in file.blocked {
+            areas.insert(cell, Ground::Blocked);
+        }
+        for cell in file.water {
+            areas.insert(cell, Ground::Water);
+        }
+        return Map::new(file.name, file.size, file.waypoints, areas);
+    }
+
+    pub fn new(name: String, size: (f32,f32), waypoints: Vec<(f32,f32)>, areas: HashMap<(i32,i32), Ground>) -> Result<Map, String> {
+        return Ok(Map {
+            name: name,
+            size: size,
+            track: Track::new(waypoints)?,
+            areas: areas,
+        });
+    }
+
+    // Writes a map in the format read by parse. The cells are sorted so saving the same map
+    // twice gives the same file.
+    pub fn to_text(name: &str, size: (f32,f32), waypoints: &[(f32,f32)], areas: &HashMap<(i32,i32), Ground>) -> Result<String, String> {
+        let cells = |ground: Ground| -> Vec<(i32,i32)> {
+            let mut cells: Vec<(i32,i32)> = areas.iter().filter(|(_, &kind)| kind == ground).map(|(&cell, _)| cell).collect();
+            cells.sort_unstable();
+            return cells;
+        };
+        let file: MapFile = MapFile {
+            name: String::from(name),
+            size: size,
+            waypoints: waypoints.to_vec(),
+            blocked: cells(Ground::Blocked),
+            water: cells(Ground::Water),
+        };
+        return ron::ser::to_string_pretty(&file, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string());
+    }
+
+    // Returns what covers each cell under the square around a circle at the given position, with
+    // None for grass
+    pub fn ground_under(&self, pos: (f32,f32), radius: f32) -> Vec<Option<Ground>> {
+        let low: (i32,i32) = cell_at((pos.0 - radius, pos.1 - radius));
+        let high: (i32,i32) = cell_at((pos.0 + radius, pos.1 + radius));
+        let mut ground: Vec<Option<Ground>> = vec![];
+        for x in low.0..=high.0 {
+            for y in low.1..=high.1 {
+                ground.push(self.areas.get(&(x,y)).copied());
+            }
+        }
+        return ground;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn position_at_segment_boundaries() {
+        let track: Track = Track::new(vec![(0.0, 0.0), (100.0, 0.0), (100.0, 50.0)]).expect("the track is valid");
+        assert_eq!(track.length(), 150.0);
+        assert_eq!(track.position_at(0.0), (0.0, 0.0));
+        assert_eq!(track.position_at(100.0), (100.0, 0.0));
+        assert_eq!(track.position_at(125.0), (100.0, 25.0));
+        assert_eq!(track.position_at(150.0), (100.0, 50.0));
+        // Distances off either end are clamped to the track
+        assert_eq!(track.position_at(-10.0), (0.0, 0.0));
+        assert_eq!(track.position_at(200.0), (100.0, 50.0));
+    }
+}
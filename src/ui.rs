@@ -0,0 +1,288 @@
+use macroquad::prelude::*;
+
+use crate::arena::Handle;
+use crate::difficulty::{Difficulty, Rules};
+use crate::enemy::Enemy;
+use crate::simulation::Simulation;
+use crate::tower::Tower;
+use crate::tower_type::{TowerId, TowerStats};
+
+// The width of the panel down the right of the screen
+const PANEL_WIDTH: f32 = 220.0;
+
+// The height of the top of the tower panel, above the upgrade buttons, where the tower's name
+// and stats are shown
+const PANEL_HEADER: f32 = 120.0;
+
+// The height of the bar across the top of the screen
+pub const HUD_HEIGHT: f32 = 36.0;
+
+// The size of the text in tooltips
+const TOOLTIP_TEXT: f32 = 20.0;
+
+// The buttons on the pause menu, from top to bottom
+pub const PAUSE_MENU: [&str; 4] = ["Resume", "Save Game", "Load Game", "Save Replay"];
+
+// Returns the area of the screen covered by the next round button
+pub fn next_round_button() -> Rect {
+    return Rect::new(screen_width() - 170.0, screen_height() - 50.0, 160.0, 40.0);
+}
+
+// Returns the area of the screen covered by the button that changes the game speed, above the
+// next round button
+pub fn speed_button() -> Rect {
+    return Rect::new(screen_width() - 170.0, screen_height() - 100.0, 160.0, 40.0);
+}
+
+// Returns the strip along the bottom of the screen holding the build panel
+pub fn build_panel() -> Rect {
+    return Rect::new(0.0, screen_height() - 70.0, screen_width(), 70.0);
+}
+
+// Returns true if a tower of the given radius at the given position would be under the HUD bar,
+// the build panel or the speed button. Towers can't be placed there, where they would be hidden.
+pub fn under_ui(pos: (f32,f32), radius: f32) -> bool {
+    if pos.1 - radius < HUD_HEIGHT || pos.1 + radius > build_panel().y {
+        return true;
+    }
+    let speed: Rect = speed_button();
+    return Rect::new(speed.x - radius, speed.y - radius, speed.w + 2.0 * radius, speed.h + 2.0 * radius).contains(Vec2::new(pos.0, pos.1));
+}
+
+// Returns the area of the screen covered by the build panel button for the given tower type, out
+// of the given number of types. The buttons run along the bottom of the screen, left of the next
+// round button, and narrow to fit on small windows.
+pub fn build_button(kind: TowerId, count: usize) -> Rect {
+    let space: f32 = next_round_button().x - 10.0;
+    let width: f32 = (space / count.max(1) as f32 - 10.0).clamp(40.0, 110.0);
+    return Rect::new(10.0 + (width + 10.0) * kind.0 as f32, screen_height() - 60.0, width, 50.0);
+}
+
+// Returns the area of the screen covered by the selected tower panel, for a tower with the given
+// number of upgrade paths
+pub fn tower_panel(paths: usize) -> Rect {
+    return Rect::new(screen_width() - PANEL_WIDTH - 10.0, HUD_HEIGHT + 10.0, PANEL_WIDTH, PANEL_HEADER + 70.0 * paths as f32 + 50.0);
+}
+
+// Returns the area of the tower panel covered by the button for the given upgrade path
+pub fn upgrade_button(path: usize) -> Rect {
+    let panel: Rect = tower_panel(0);
+    return Rect::new(panel.x + 10.0, panel.y + PANEL_HEADER + 70.0 * path as f32, panel.w - 20.0, 60.0);
+}
+
+// Returns the area of the tower panel covered by the sell button, below the buttons for the given
+// number of upgrade paths
+pub fn sell_button(paths: usize) -> Rect {
+    let panel: Rect = tower_panel(0);
+    return Rect::new(panel.x + 10.0, panel.y + PANEL_HEADER + 70.0 * paths as f32, panel.w - 20.0, 40.0);
+}
+
+// Returns the area of the screen covered by the given pause menu button
+pub fn pause_menu_button(button: usize) -> Rect {
+    return Rect::new(screen_width() / 2.0 - 100.0, screen_height() / 3.0 + 60.0 * button as f32, 200.0, 44.0);
+}
+
+// Returns the area of the screen covered by the difficulty menu button for the given difficulty
+pub fn difficulty_button(difficulty: usize) -> Rect {
+    return Rect::new(screen_width() / 2.0 - 160.0, screen_height() / 4.0 + 70.0 * difficulty as f32, 320.0, 58.0);
+}
+
+// Draws the menu for choosing the difficulty of a new game over the top of the game, with the
+// rules each difficulty sets
+pub fn draw_difficulty_menu(rounds: u32) {
+    draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::new(0.0, 0.0, 0.0, 0.75));
+    let dims = measure_text("Choose a Difficulty", None, 48, 1.0);
+    draw_text("Choose a Difficulty", (screen_width() - dims.width) / 2.0, screen_height() / 4.0 - 30.0, 48.0, WHITE);
+    for (i, difficulty) in Difficulty::ALL.iter().enumerate() {
+        let rules: Rules = difficulty.rules();
+        let name: String = format!("{}  [{}]", difficulty.name(), i + 1);
+        let details: String = format!("{} lives  ${}  {} rounds  costs x{}", rules.lives, rules.money, rules.rounds.min(rounds), rules.cost_factor);
+        draw_button(difficulty_button(i), &[&name, &details], true);
+    }
+    draw_text("F9 to load a saved game instead", screen_width() / 2.0 - 140.0, screen_height() / 4.0 + 300.0, 20.0, GRAY);
+}
+
+// Draws the pause menu over the top of the game
+pub fn draw_pause_menu() {
+    draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::new(0.0, 0.0, 0.0, 0.6));
+    let dims = measure_text("Paused", None, 48, 1.0);
+    draw_text("Paused", (screen_width() - dims.width) / 2.0, screen_height() / 3.0 - 30.0, 48.0, WHITE);
+    for (i, label) in PAUSE_MENU.iter().enumerate() {
+        draw_button(pause_menu_button(i), &[label], true);
+    }
+    draw_text("Esc to resume, F5 to save, F9 to load, F6 to save a replay", screen_width() / 2.0 - 220.0, screen_height() / 3.0 + 260.0, 20.0, GRAY);
+}
+
+// Draws the bar across the top of the screen showing lives, money, the round, the difficulty and
+// how fast the game is running. The items are spread evenly across the bar and the text shrinks
+// on narrow windows so they don't run into each other.
+pub fn draw_hud(sim: &Simulation, speed: u32, paused: bool) {
+    let width: f32 = screen_width();
+    draw_rectangle(0.0, 0.0, width, HUD_HEIGHT, Color::new(0.1, 0.1, 0.1, 0.85));
+    let speed_text: String = if paused { String::from("Paused") } else { format!("Speed: {}x", speed) };
+    let items: [(String, Color); 5] = [
+        (format!("Lives: {}", sim.health), if sim.health > 20 { WHITE } else { RED }),
+        (format!("Cash: ${}", sim.money), GOLD),
+        (format!("Round: {}/{}", sim.round + sim.in_round as u32, sim.total_rounds()), WHITE),
+        (format!("Difficulty: {}", sim.difficulty.name()), WHITE),
+        (speed_text, if paused { ORANGE } else { WHITE }),
+    ];
+    let column: f32 = width / items.len() as f32;
+    let size: f32 = (column / 8.0).clamp(14.0, 26.0);
+    for (i, (text, colour)) in items.iter().enumerate() {
+        draw_text(text, 10.0 + column * i as f32, HUD_HEIGHT / 2.0 + size / 3.0, size, *colour);
+    }
+}
+
+// Draws a panel next to the mouse describing the enemy or tower under it, if there is one. The
+// panel is kept on screen when the mouse is near an edge.
+pub fn draw_tooltip(sim: &Simulation, mouse: (f32,f32)) {
+    let lines: Vec<String> = if let Some(tower) = sim.tower_at(mouse.0, mouse.1).and_then(|tower| sim.towers.get(tower)) {
+        tower_tooltip(sim, tower)
+    } else if let Some(enemy) = sim.enemy_at(mouse.0, mouse.1).and_then(|enemy| sim.enemies.get(enemy)) {
+        enemy_tooltip(sim, enemy)
+    } else {
+        return;
+    };
+    let width: f32 = lines.iter().map(|line| measure_text(line, None, TOOLTIP_TEXT as u16, 1.0).width).fold(0.0, f32::max) + 20.0;
+    let height: f32 = lines.len() as f32 * TOOLTIP_TEXT + 12.0;
+    let x: f32 = (mouse.0 + 16.0).min(screen_width() - width).max(0.0);
+    let y: f32 = (mouse.1 + 16.0).min(screen_height() - height).max(HUD_HEIGHT);
+    draw_rectangle(x, y, width, height, Color::new(0.05, 0.05, 0.05, 0.9));
+    draw_rectangle_lines(x, y, width, height, 1.0, GRAY);
+    for (i, line) in lines.iter().enumerate() {
+        let colour: Color = if i == 0 { YELLOW } else { WHITE };
+        draw_text(line, x + 10.0, y + TOOLTIP_TEXT * (i + 1) as f32, TOOLTIP_TEXT, colour);
+    }
+}
+
+fn tower_tooltip(sim: &Simulation, tower: &Tower) -> Vec<String> {
+    let tiers: Vec<String> = tower.tiers.iter().map(|tier| tier.to_string()).collect();
+    let mut lines: Vec<String> = vec![
+        sim.tower_types.get(tower.kind).name.clone(),
+        format!("Targeting: {}", tower.targeting.name()),
+        format!("Range: {}", range_text(&tower.stats)),
+        format!("Upgrades: {}", tiers.join("-")),
+        format!("Popped: {}", tower.popped),
+        format!("Sells for: ${}", sim.sell_value(tower)),
+    ];
+    if tower.stats.camo {
+        lines.push(String::from("Detects camo"));
+    }
+    return lines;
+}
+
+fn enemy_tooltip(sim: &Simulation, enemy: &Enemy) -> Vec<String> {
+    let mut name: String = String::new();
+    for (has, modifier) in [(enemy.modifiers.camo, "Camo "), (enemy.modifiers.regrow, "Regrow "), (enemy.modifiers.fortified, "Fortified "), (enemy.modifiers.lead, "Lead ")] {
+        if has {
+            name.push_str(modifier);
+        }
+    }
+    name.push_str(&sim.bloons.get(enemy.kind).name);
+    let mut lines: Vec<String> = vec![
+        name,
+        format!("Layers: {}", enemy.layers),
+        format!("Speed: {:.0}", enemy.speed * sim.difficulty.rules().speed_factor * enemy.statuses.speed_factor()),
+    ];
+    for (has, status) in [(enemy.statuses.slowed(), "Slowed"), (enemy.statuses.frozen(), "Frozen"), (enemy.statuses.burning(), "Burning"), (enemy.statuses.stunned(), "Stunned")] {
+        if has {
+            lines.push(String::from(status));
+        }
+    }
+    return lines;
+}
+
+// Draws a button for every tower type, each showing its hotkey, name and cost. The type being
+// placed is outlined.
+pub fn draw_build_panel(sim: &Simulation, build: Option<TowerId>) {
+    let count: usize = sim.tower_types.len();
+    for kind in (0..count).map(TowerId) {
+        let tower_type = sim.tower_types.get(kind);
+        let button: Rect = build_button(kind, count);
+        let name: String = if kind.0 < 9 { format!("{} {}", kind.0 + 1, tower_type.name) } else { tower_type.name.clone() };
+        let cost: u32 = sim.price(tower_type.cost);
+        let (background, text) = if cost <= sim.money { (DARKGREEN, WHITE) } else { (DARKGRAY, GRAY) };
+        draw_rectangle(button.x, button.y, button.w, button.h, background);
+        draw_text(&name, button.x + 6.0, button.y + 20.0, fit_text(&name, button.w - 12.0, 18.0), text);
+        draw_text(&format!("${}", cost), button.x + 6.0, button.y + 42.0, 20.0, text);
+        if build == Some(kind) {
+            draw_rectangle_lines(button.x, button.y, button.w, button.h, 3.0, YELLOW);
+        }
+    }
+}
+
+// Draws the button that changes the game speed, showing the speed it is at
+pub fn draw_speed_button(speed: u32, halted: bool) {
+    if halted {
+        draw_button(speed_button(), &["Stopped  [P] [N]"], false);
+        return;
+    }
+    draw_button(speed_button(), &[&format!("Speed {}x  [F]", speed)], true);
+}
+
+// Returns the largest text size up to the given one that fits the text in the given width
+fn fit_text(text: &str, width: f32, size: f32) -> f32 {
+    let text_width: f32 = measure_text(text, None, size as u16, 1.0).width;
+    if text_width <= width {
+        return size;
+    }
+    return (size * width / text_width).floor().max(8.0);
+}
+
+// Draws a button with up to two lines of text, greyed out when it can't be pressed
+pub fn draw_button(rect: Rect, lines: &[&str], enabled: bool) {
+    let (background, text) = if enabled { (DARKGREEN, WHITE) } else { (DARKGRAY, GRAY) };
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, background);
+    for (i, line) in lines.iter().enumerate() {
+        draw_text(line, rect.x + 8.0, rect.y + 24.0 + 24.0 * i as f32, 22.0, text);
+    }
+}
+
+// Returns the range of a tower as shown to the player
+fn range_text(stats: &TowerStats) -> String {
+    if stats.global {
+        return String::from("Global");
+    }
+    return format!("{:.0}", stats.range);
+}
+
+// Draws the range of the selected tower, unless it can reach the whole map, then the panel showing its stats, targetting, what it
+// has popped, the next upgrade on each path and what it sells for. Each button is labelled with
+// its keyboard shortcut.
+pub fn draw_tower_panel(sim: &Simulation, tower: Handle, upgrade_keys: &[&str]) {
+    let Some(tower) = sim.towers.get(tower) else {
+        return;
+    };
+    if !tower.stats.global {
+        draw_circle(tower.x, tower.y, tower.stats.range, Color::new(1.0, 1.0, 1.0, 0.08));
+        draw_circle_lines(tower.x, tower.y, tower.stats.range, 2.0, WHITE);
+    }
+    let tower_type = sim.tower_types.get(tower.kind);
+    let stats = &tower.stats;
+    let panel: Rect = tower_panel(tower.tiers.len());
+    draw_rectangle(panel.x, panel.y, panel.w, panel.h, Color::new(0.1, 0.1, 0.1, 0.9));
+    draw_text(&tower_type.name, panel.x + 10.0, panel.y + 28.0, 28.0, WHITE);
+    let lines: [String; 3] = [
+        format!("Targeting: {} [Tab]  Popped: {}", tower.targeting.name(), tower.popped),
+        format!("Range: {}  Shots: {:.1}/s", range_text(stats), 1.0 / stats.cooldown.max(0.01)),
+        format!("Damage: {} {:?}  Pierce: {}{}", stats.projectile.damage, stats.projectile.damage_type, stats.projectile.pierce, if stats.camo { "  Camo" } else { "" }),
+    ];
+    for (i, line) in lines.iter().enumerate() {
+        draw_text(line, panel.x + 10.0, panel.y + 54.0 + 20.0 * i as f32, fit_text(line, panel.w - 20.0, 18.0), WHITE);
+    }
+    for path in 0..tower.tiers.len() {
+        let button: Rect = upgrade_button(path);
+        let key: &str = upgrade_keys.get(path).copied().unwrap_or("");
+        match tower_type.next_upgrade(&tower.tiers, path) {
+            Some(upgrade) => {
+                let cost: u32 = sim.price(upgrade.cost);
+                let label: String = format!("${}  (tier {})  {}", cost, tower.tiers[path] + 1, key);
+                draw_button(button, &[&upgrade.name, &label], cost <= sim.money);
+            }
+            None => draw_button(button, &["Fully upgraded"], false),
+        }
+    }
+    let sell: String = format!("Sell for ${}  [Del]", sim.sell_value(tower));
+    draw_button(sell_button(tower.tiers.len()), &[&sell], true);
+}